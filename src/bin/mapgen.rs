@@ -0,0 +1,226 @@
+// Builds levels without opening a window, and writes each one out as
+// text (and, if asked, as a picture) so generator output can be looked
+// over in bulk and diffed between changes.
+//
+//     cargo run --bin mapgen -- --generator maze --seed 7 --count 10 --out maps --png
+//
+// Level n of a run is built from seed + n, so any one of them can be
+// built again on its own with --count 1.
+
+use map_making::{ map_builders, TileType };
+use map_making::map_builders::BuilderMap;
+
+use rltk::RandomNumberGenerator;
+
+use std::error::Error;
+use std::fs::{ self, File };
+use std::io::BufWriter;
+use std::path::{ Path, PathBuf };
+
+const PNG_TILE_SIZE: u32 = 4; // pixels across each tile takes up
+
+const USAGE: &str = "\
+usage: mapgen [options]
+
+  --generator <name>   which generator to run (default random)
+  --seed <n>           seed for the first level (default 1)
+  --count <n>          how many levels to build (default 1)
+  --width <n>          map width in tiles (default 120)
+  --height <n>         map height in tiles (default 70)
+  --depth <n>          dungeon depth to build for (default 1)
+  --out <dir>          write a file per level here, instead of to stdout
+  --png                write a picture of each level as well (needs --out)
+  --list               list the generators and stop";
+
+struct Options {
+    generator: String,
+    seed: u64,
+    count: u64,
+    width: i32,
+    height: i32,
+    depth: i32,
+    out: Option<PathBuf>,
+    png: bool,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.iter().any(|arg| arg == "--list") {
+        for name in map_builders::BUILDER_NAMES {
+            println!("{}", name);
+        }
+        return;
+    }
+
+    let options = match parse_options(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(&options) {
+        eprintln!("mapgen: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        generator: "random".to_string(),
+        seed: 1,
+        count: 1,
+        width: 120,
+        height: 70,
+        depth: 1,
+        out: None,
+        png: false,
+    };
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+
+        match arg.as_str() {
+            "--generator" => options.generator = value()?.clone(),
+            "--seed" => options.seed = parse_number(arg, value()?)?,
+            "--count" => options.count = parse_number(arg, value()?)?,
+            "--width" => options.width = parse_number(arg, value()?)?,
+            "--height" => options.height = parse_number(arg, value()?)?,
+            "--depth" => options.depth = parse_number(arg, value()?)?,
+            "--out" => options.out = Some(PathBuf::from(value()?)),
+            "--png" => options.png = true,
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+
+    if !map_builders::BUILDER_NAMES.contains(&options.generator.as_str()) {
+        return Err(format!("unknown generator {} (try --list)", options.generator));
+    }
+
+    // the smallest any generator will cope with
+    if options.width < 20 || options.height < 20 {
+        return Err("maps must be at least 20 tiles in each direction".to_string());
+    }
+
+    if options.depth < 1 {
+        return Err("--depth starts at 1".to_string());
+    }
+
+    // the last level's seed has to fit as well as the first's
+    if options.count > 0 && options.seed.checked_add(options.count - 1).is_none() {
+        return Err(format!("--seed {} with --count {} runs past the largest seed, {}",
+                           options.seed, options.count, u64::MAX));
+    }
+
+    if options.png && options.out.is_none() {
+        return Err("--png needs an --out directory to write to".to_string());
+    }
+
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{} needs a whole number, not {}", arg, value))
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = &options.out {
+        fs::create_dir_all(dir)?;
+    }
+
+    for n in 0..options.count {
+        // checked when the options were read
+        let seed = options.seed + n;
+        let mut rng = RandomNumberGenerator::seeded(seed);
+        let mut builder = map_builders::named_builder(&options.generator, options.depth,
+                                                      options.width, options.height, &mut rng)
+            .ok_or(format!("{} can't build a {}x{} level", options.generator,
+                           options.width, options.height))?;
+        builder.build_map(&mut rng);
+
+        // the same check the game makes, which walls off anything that
+        // can't be reached so the dump matches what would be played
+        let playable = builder.validate();
+        let text = map_builders::level_to_text(&builder.build_data);
+
+        match &options.out {
+            None => {
+                println!("{} seed {}{}", options.generator, seed,
+                         if playable { "" } else { " (rejected)" });
+                println!("{}", text);
+            }
+            Some(dir) => {
+                let name = format!("{}_{}", options.generator, seed);
+                fs::write(dir.join(format!("{}.txt", name)), text)?;
+
+                if options.png {
+                    write_png(&builder.build_data, &dir.join(format!("{}.png", name)))?;
+                }
+
+                if !playable {
+                    eprintln!("{} seed {} would be rejected", options.generator, seed);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_png(build_data: &BuilderMap, path: &Path) -> Result<(), Box<dyn Error>> {
+    let map = &build_data.map;
+    let width = map.width as u32 * PNG_TILE_SIZE;
+    let height = map.height as u32 * PNG_TILE_SIZE;
+
+    let mut colours: Vec<[u8; 3]> = map.tiles.iter().map(|tile| tile_colour(*tile)).collect();
+
+    for (idx, name) in build_data.spawn_list.iter() {
+        colours[*idx] = match name.as_str() {
+            "Goblin" | "Ork" => [255, 0, 0],
+            "Door" => [139, 69, 19],
+            _ => [255, 0, 255], // items
+        };
+    }
+
+    if let Some(start) = build_data.starting_position {
+        colours[map.xy_idx(start.x, start.y)] = [255, 255, 0];
+    }
+
+    // every tile is a square block of its colour
+    let mut data = Vec::with_capacity((width * height * 3) as usize);
+
+    for y in 0..height {
+        for x in 0..width {
+            let idx = map.xy_idx((x / PNG_TILE_SIZE) as i32, (y / PNG_TILE_SIZE) as i32);
+            data.extend_from_slice(&colours[idx]);
+        }
+    }
+
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+
+    Ok(())
+}
+
+fn tile_colour(tt: TileType) -> [u8; 3] {
+    match tt {
+        TileType::Wall => [0, 96, 0],
+        TileType::Floor => [0, 128, 128],
+        TileType::DownStairs | TileType::UpStairs => [0, 255, 255],
+        TileType::ShallowWater => [76, 153, 255],
+        TileType::DeepWater => [0, 0, 255],
+        TileType::Lava => [255, 76, 0],
+        TileType::Grass => [0, 204, 0],
+        TileType::Bridge => [153, 102, 51],
+        TileType::Rubble => [128, 128, 102],
+        TileType::GlassWall => [153, 230, 255],
+    }
+}
@@ -0,0 +1,163 @@
+use super::{ Map, TileType, Position, Renderable };
+
+use rltk::{ Rltk, RGB, Point };
+use specs::prelude::*;
+
+const VIEW_WIDTH: i32 = 80; // the map view uses the full console width
+const VIEW_HEIGHT: i32 = 43; // everything below this is the log panel
+
+/// The part of the map currently on screen, as map coordinates:
+/// `(min_x, max_x, min_y, max_y)`, with the maximums exclusive. The
+/// view is kept centered on the player.
+pub fn get_screen_bounds(ecs: &World) -> (i32, i32, i32, i32) {
+    let player_pos = ecs.fetch::<Point>();
+
+    let min_x = player_pos.x - VIEW_WIDTH / 2;
+    let min_y = player_pos.y - VIEW_HEIGHT / 2;
+
+    (min_x, min_x + VIEW_WIDTH, min_y, min_y + VIEW_HEIGHT)
+}
+
+/// Draws the part of the map around the player, along with every
+/// entity the player can see in it.
+pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
+    let map = ecs.fetch::<Map>();
+    let (min_x, max_x, min_y, max_y) = get_screen_bounds(ecs);
+
+    for (screen_y, y) in (min_y..max_y).enumerate() {
+        for (screen_x, x) in (min_x..max_x).enumerate() {
+            if x >= 0 && x < map.width && y >= 0 && y < map.height {
+                let idx = map.xy_idx(x, y);
+
+                if map.revealed_tiles[idx] {
+                    let (glyph, fg, bg) = get_tile_glyph(map.tiles[idx], map.visible_tiles[idx]);
+                    ctx.set(screen_x as i32, screen_y as i32, fg, bg, glyph);
+                }
+            } else {
+                // the view has run off the edge of the map
+                ctx.set(screen_x as i32, screen_y as i32, RGB::named(rltk::GRAY),
+                        RGB::named(rltk::BLACK), rltk::to_cp437('·'));
+            }
+        }
+    }
+
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+
+    let mut data = (&positions, &renderables).join().collect::<Vec<_>>();
+    data.sort_by_key(|(_, render)| std::cmp::Reverse(render.render_order));
+
+    for (pos, render) in data.iter() {
+        let idx = map.xy_idx(pos.x, pos.y);
+        let on_screen = pos.x >= min_x && pos.x < max_x && pos.y >= min_y && pos.y < max_y;
+
+        if on_screen && map.visible_tiles[idx] {
+            ctx.set(pos.x - min_x, pos.y - min_y, render.fg, render.bg, render.glyph);
+        }
+    }
+}
+
+/// Draws one of the generation visualiser's snapshots of the level's
+/// tiles. Maps too big for the view are scaled down until the whole
+/// of it fits, each cell on screen standing for a square of tiles.
+pub fn render_snapshot(ecs: &World, tiles: &[TileType], ctx: &mut Rltk) {
+    let map = ecs.fetch::<Map>();
+
+    // rounded up, so the last partial square still fits
+    let scale = i32::max((map.width + VIEW_WIDTH - 1) / VIEW_WIDTH,
+                         (map.height + VIEW_HEIGHT - 1) / VIEW_HEIGHT).max(1);
+    let scaled_width = (map.width + scale - 1) / scale;
+    let scaled_height = (map.height + scale - 1) / scale;
+    let offset_x = (VIEW_WIDTH - scaled_width) / 2;
+    let offset_y = (VIEW_HEIGHT - scaled_height) / 2;
+
+    for screen_y in 0..scaled_height {
+        for screen_x in 0..scaled_width {
+            let square = (screen_y * scale..i32::min((screen_y + 1) * scale, map.height))
+                .flat_map(|y| (screen_x * scale..i32::min((screen_x + 1) * scale, map.width))
+                    .map(move |x| (x, y)));
+
+            // stairs and open ground matter more than the walls around them
+            let tile = square.map(|(x, y)| tiles[map.xy_idx(x, y)])
+                .min_by_key(|tile| match tile {
+                    TileType::DownStairs | TileType::UpStairs => 0,
+                    TileType::Wall => 2,
+                    _ => 1,
+                });
+
+            if let Some(tile) = tile {
+                let (glyph, fg, bg) = get_tile_glyph(tile, true);
+                ctx.set(offset_x + screen_x, offset_y + screen_y, fg, bg, glyph);
+            }
+        }
+    }
+}
+
+fn get_tile_glyph(tile: TileType, visible: bool) -> (rltk::FontCharType, RGB, RGB) {
+    let glyph;
+    let mut fg;
+
+    match tile {
+        TileType::Floor => {
+            glyph = rltk::to_cp437('.');
+            fg = RGB::from_f32(0.0, 0.5, 0.5);
+        }
+
+        TileType::Wall => {
+            glyph = rltk::to_cp437('#');
+            fg = RGB::from_f32(0.0, 1.0, 0.0);
+        }
+
+        TileType::DownStairs => {
+            glyph = rltk::to_cp437('>');
+            fg = RGB::from_f32(0.0, 1.0, 1.0);
+        }
+
+        TileType::UpStairs => {
+            glyph = rltk::to_cp437('<');
+            fg = RGB::from_f32(0.0, 1.0, 1.0);
+        }
+
+        TileType::ShallowWater => {
+            glyph = rltk::to_cp437('~');
+            fg = RGB::from_f32(0.3, 0.6, 1.0);
+        }
+
+        TileType::DeepWater => {
+            glyph = rltk::to_cp437('≈');
+            fg = RGB::from_f32(0.0, 0.0, 1.0);
+        }
+
+        TileType::Lava => {
+            glyph = rltk::to_cp437('≈');
+            fg = RGB::from_f32(1.0, 0.3, 0.0);
+        }
+
+        TileType::Grass => {
+            glyph = rltk::to_cp437('"');
+            fg = RGB::from_f32(0.0, 0.8, 0.0);
+        }
+
+        TileType::Bridge => {
+            glyph = rltk::to_cp437('=');
+            fg = RGB::from_f32(0.6, 0.4, 0.2);
+        }
+
+        TileType::Rubble => {
+            glyph = rltk::to_cp437(';');
+            fg = RGB::from_f32(0.5, 0.5, 0.4);
+        }
+
+        TileType::GlassWall => {
+            glyph = rltk::to_cp437('#');
+            fg = RGB::from_f32(0.6, 0.9, 1.0);
+        }
+    }
+
+    // remembered but out of sight
+    if !visible {
+        fg = fg.to_greyscale();
+    }
+
+    (glyph, fg, RGB::from_f32(0., 0., 0.))
+}
@@ -2,7 +2,7 @@ use specs::prelude::*;
 use specs_derive::*;
 use rltk::{RGB};
 
-#[derive(Component)]
+#[derive(Component, Clone, Copy)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -13,6 +13,8 @@ pub struct Renderable {
     pub glyph: rltk::FontCharType,
     pub fg: RGB,
     pub bg: RGB,
+    // lower numbers are drawn on top when entities share a tile
+    pub render_order: i32,
 }
 
 #[derive(Component, Debug)]
@@ -36,6 +38,13 @@ pub struct Name{
 #[derive(Component, Debug)]
 pub struct BlocksTile {}
 
+// A closed door blocks movement and sight until something bumps
+// into it, which opens it for good.
+#[derive(Component, Debug)]
+pub struct Door {
+    pub open: bool,
+}
+
 #[derive(Component, Debug)]
 pub struct CombatStats {
     pub max_hp: i32,
@@ -65,4 +74,78 @@ impl SufferDamage {
             store.insert(victim, dmg).expect("Unable to insert damage.");
         }
     }
-}
\ No newline at end of file
+}
+
+// Something that can be picked up and carried.
+#[derive(Component, Debug)]
+pub struct Item {}
+
+// An item being carried. It has no `Position` while it is.
+#[derive(Component, Debug, Clone)]
+pub struct InBackpack {
+    pub owner: Entity,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct WantsToPickupItem {
+    pub collected_by: Entity,
+    pub item: Entity,
+}
+
+// Used up the first time it is used.
+#[derive(Component, Debug)]
+pub struct Consumable {}
+
+// Restores hit points to whoever the item is used on, up to their
+// maximum.
+#[derive(Component, Debug)]
+pub struct ProvidesHealing {
+    pub heal_amount: i32,
+}
+
+// Used on a tile picked from those in sight, up to this many tiles
+// away, rather than on whoever uses it.
+#[derive(Component, Debug)]
+pub struct Ranged {
+    pub range: i32,
+}
+
+// Hurts whoever the item is used on.
+#[derive(Component, Debug)]
+pub struct InflictsDamage {
+    pub damage: i32,
+}
+
+// Hits everything within this many tiles of where the item is aimed,
+// short of anything a blast can't get past.
+#[derive(Component, Debug)]
+pub struct AreaOfEffect {
+    pub radius: i32,
+}
+
+// On an item, confuses monsters it is used on for this many turns.
+// On a monster, how many more turns it will lose to confusion.
+#[derive(Component, Debug, Clone)]
+pub struct Confusion {
+    pub turns: i32,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct WantsToUseItem {
+    pub item: Entity,
+    pub target: Option<rltk::Point>,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct WantsToDropItem {
+    pub item: Entity,
+}
+
+// Where an entity is on a level the player isn't on. It takes the
+// place of `Position` until the player comes back to that level.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct OtherLevelPosition {
+    pub x: i32,
+    pub y: i32,
+    pub depth: i32,
+}
@@ -0,0 +1,79 @@
+use super::{ Map, Position, OtherLevelPosition, Player, Viewshed, InBackpack };
+
+use specs::prelude::*;
+
+use std::collections::HashMap;
+
+/// Every level the player has left, exactly as they left it, so
+/// coming back restores it instead of building a new one.
+#[derive(Default)]
+pub struct MasterDungeonMap {
+    maps: HashMap<i32, Map>,
+}
+
+impl MasterDungeonMap {
+    pub fn new() -> MasterDungeonMap {
+        MasterDungeonMap{ maps: HashMap::new() }
+    }
+
+    pub fn store_map(&mut self, map: &Map) {
+        self.maps.insert(map.depth, map.clone());
+    }
+
+    pub fn get_map(&self, depth: i32) -> Option<Map> {
+        self.maps.get(&depth).cloned()
+    }
+}
+
+/// Takes everything but the player off the current level, keeping
+/// where it was in an `OtherLevelPosition`. Systems only look at
+/// entities with a `Position`, so frozen entities sit still. Anything
+/// being carried goes along with whoever carries it.
+pub fn freeze_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let players = ecs.read_storage::<Player>();
+    let backpacks = ecs.read_storage::<InBackpack>();
+    let depth = ecs.fetch::<Map>().depth;
+
+    let mut frozen: Vec<Entity> = Vec::new();
+
+    for (entity, pos, _, _) in (&entities, &positions, !&players, !&backpacks).join() {
+        other_level_positions.insert(entity, OtherLevelPosition{ x: pos.x, y: pos.y, depth })
+            .expect("Unable to freeze entity");
+        frozen.push(entity);
+    }
+
+    for entity in frozen {
+        positions.remove(entity);
+    }
+}
+
+/// Puts back everything that was frozen on the current level.
+pub fn thaw_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let mut viewsheds = ecs.write_storage::<Viewshed>();
+    let depth = ecs.fetch::<Map>().depth;
+
+    let mut thawed: Vec<Entity> = Vec::new();
+
+    for (entity, pos) in (&entities, &other_level_positions).join() {
+        if pos.depth == depth {
+            positions.insert(entity, Position{ x: pos.x, y: pos.y })
+                .expect("Unable to thaw entity");
+            thawed.push(entity);
+        }
+    }
+
+    for entity in thawed {
+        other_level_positions.remove(entity);
+
+        // whatever they saw before the player left is out of date
+        if let Some(viewshed) = viewsheds.get_mut(entity) {
+            viewshed.dirty = true;
+        }
+    }
+}
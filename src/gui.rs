@@ -1,11 +1,15 @@
-use super::{ CombatStats, Player, GameLog };
+use super::{ CombatStats, Player, GameLog, Map, Name, InBackpack, Viewshed, camera };
 
-use rltk::{ RGB, Rltk, Console };
+use rltk::{ RGB, Rltk, VirtualKeyCode, Point, DistanceAlg };
 use specs::prelude::*;
 
 pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
     ctx.draw_box(0, 43, 79, 6, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
 
+    let map = ecs.fetch::<Map>();
+    let depth = format!("Depth: {}", map.depth);
+    ctx.print_color(2, 43, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), &depth);
+
     let combat_stats = ecs.read_storage::<CombatStats>();
     let players = ecs.read_storage::<Player>();
 
@@ -21,13 +25,181 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
 
     let log = ecs.fetch::<GameLog>();
 
-    let mut y = 44;
+    for (y, s) in (44..49).zip(log.entries.iter().rev()) {
+        ctx.print(2, y, s);
+    }
+}
+
+/// The status line shown while the generation visualiser plays.
+pub fn draw_mapgen_progress(ctx: &mut Rltk, step: usize, total: usize) {
+    let progress = format!("Generating level: step {} of {} (any key to skip)", step, total);
+    ctx.print_color(2, 45, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), &progress);
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ItemMenuResult {
+    Cancel,
+    NoResponse,
+    Selected,
+}
+
+/// Lists what the player is carrying over the top of the map, each
+/// item against a letter to pick it with. Escape backs out.
+pub fn show_inventory(ecs: &World, ctx: &mut Rltk, title: &str) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let entities = ecs.entities();
+
+    let inventory: Vec<(Entity, &Name)> = (&entities, &backpack, &names).join()
+        .filter(|(_, item, _)| item.owner == *player_entity)
+        .map(|(entity, _, name)| (entity, name))
+        .collect();
+
+    let count = inventory.len() as i32;
+    let y = 25 - (count / 2);
+
+    ctx.draw_box(15, y - 2, 31, count + 3, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    ctx.print_color(18, y - 2, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), title);
+    ctx.print_color(18, y + count + 1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK),
+                    "ESCAPE to cancel");
+
+    if inventory.is_empty() {
+        ctx.print(17, y, "You aren't carrying anything.");
+    }
+
+    for (j, (_, name)) in inventory.iter().enumerate() {
+        let row = y + j as i32;
+
+        ctx.set(17, row, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
+        ctx.set(18, row, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), 97 + j as rltk::FontCharType);
+        ctx.set(19, row, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
+
+        ctx.print(21, row, &name.name);
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(VirtualKeyCode::Escape) => (ItemMenuResult::Cancel, None),
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+
+            if selection > -1 && selection < count {
+                return (ItemMenuResult::Selected, Some(inventory[selection as usize].0));
+            }
+
+            (ItemMenuResult::NoResponse, None)
+        }
+    }
+}
+
+/// Where the targeting cursor is on the map. It starts on the nearest
+/// thing worth hitting, moves with the movement keys, and jumps to the
+/// mouse whenever the mouse moves.
+pub struct TargetCursor {
+    pos: Option<Point>,
+    mouse: (i32, i32),
+}
+
+impl TargetCursor {
+    pub fn new(mouse: (i32, i32)) -> TargetCursor {
+        TargetCursor{ pos: None, mouse }
+    }
+}
+
+/// Highlights every tile in sight and in range with a clear line of
+/// fire, and lets the player pick one with the cursor: Enter or a left
+/// click fires, Escape backs out.
+pub fn ranged_target(ecs: &World, ctx: &mut Rltk, range: i32,
+                     cursor: &mut TargetCursor) -> (ItemMenuResult, Option<Point>) {
+    let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(ecs);
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = ecs.fetch::<Point>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    let map = ecs.fetch::<Map>();
+
+    ctx.print_color(5, 0, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK),
+                    "Select Target: ENTER or click to fire, ESCAPE to cancel");
+
+    let mut available_cells: Vec<Point> = Vec::new();
 
-    for s in log.entries.iter().rev() {
-        if y < 49 {
-            ctx.print(2, y, s);
+    if let Some(visible) = viewsheds.get(*player_entity) {
+        for pt in visible.visible_tiles.iter() {
+            let distance = DistanceAlg::Pythagoras.distance2d(*player_pos, *pt);
+
+            if distance <= range as f32 && map.has_line_of_fire(*player_pos, *pt) {
+                available_cells.push(*pt);
+                ctx.set_bg(pt.x - min_x, pt.y - min_y, RGB::named(rltk::BLUE));
+            }
         }
+    } else {
+        return (ItemMenuResult::Cancel, None);
+    }
+
+    // the camera is centred on the player, so the mouse has to be
+    // moved back onto the map
+    let mouse = ctx.mouse_pos();
+    let mouse_target = Point::new(mouse.0 + min_x, mouse.1 + min_y);
+
+    if mouse != cursor.mouse {
+        cursor.mouse = mouse;
+        cursor.pos = Some(mouse_target);
+    }
+
+    let mut target = cursor.pos.unwrap_or_else(|| {
+        let nearest_enemy = available_cells.iter()
+            .filter(|pt| {
+                map.tile_content[map.xy_idx(pt.x, pt.y)].iter()
+                    .any(|entity| *entity != *player_entity && combat_stats.contains(*entity))
+            })
+            .min_by(|a, b| {
+                let distance = |pt: &Point| DistanceAlg::PythagorasSquared.distance2d(*player_pos, *pt);
+                distance(a).partial_cmp(&distance(b)).unwrap()
+            });
+
+        *nearest_enemy.unwrap_or(&player_pos)
+    });
+
+    if let Some((dx, dy)) = ctx.key.and_then(cursor_delta) {
+        target.x = (target.x + dx).clamp(min_x, max_x - 1);
+        target.y = (target.y + dy).clamp(min_y, max_y - 1);
+    }
+
+    cursor.pos = Some(target);
+
+    let valid_target = available_cells.contains(&target);
+    let cursor_colour = if valid_target { rltk::CYAN } else { rltk::RED };
+    ctx.set_bg(target.x - min_x, target.y - min_y, RGB::named(cursor_colour));
+
+    if ctx.left_click {
+        if available_cells.contains(&mouse_target) {
+            return (ItemMenuResult::Selected, Some(mouse_target));
+        }
+
+        return (ItemMenuResult::Cancel, None);
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => (ItemMenuResult::Cancel, None),
+        Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::NumpadEnter) if valid_target =>
+            (ItemMenuResult::Selected, Some(target)),
+        _ => (ItemMenuResult::NoResponse, None),
+    }
+}
 
-        y += 1;
+/// How far a key moves the targeting cursor, using the same keys as
+/// moving the player.
+fn cursor_delta(key: VirtualKeyCode) -> Option<(i32, i32)> {
+    match key {
+        VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => Some((-1, 0)),
+        VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => Some((1, 0)),
+        VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => Some((0, -1)),
+        VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => Some((0, 1)),
+        VirtualKeyCode::Numpad9 | VirtualKeyCode::Y => Some((1, -1)),
+        VirtualKeyCode::Numpad7 | VirtualKeyCode::U => Some((-1, -1)),
+        VirtualKeyCode::Numpad3 | VirtualKeyCode::N => Some((1, 1)),
+        VirtualKeyCode::Numpad1 | VirtualKeyCode::B => Some((-1, 1)),
+        _ => None,
     }
 }
@@ -0,0 +1,408 @@
+use super::{ WantsToPickupItem, WantsToUseItem, WantsToDropItem, Name, InBackpack,
+             Position, GameLog, Consumable, ProvidesHealing, InflictsDamage,
+             CombatStats, SufferDamage, Map, AreaOfEffect, Confusion, Monster };
+
+use specs::prelude::*;
+
+pub struct ItemCollectionSystem {}
+
+impl<'a> System<'a> for ItemCollectionSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (ReadExpect<'a, Entity>,
+                       WriteExpect<'a, GameLog>,
+                       WriteStorage<'a, WantsToPickupItem>,
+                       WriteStorage<'a, Position>,
+                       ReadStorage<'a, Name>,
+                       WriteStorage<'a, InBackpack>);
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity,
+             mut gamelog,
+             mut wants_pickup,
+             mut positions,
+             names,
+             mut backpack) = data;
+
+        for pickup in wants_pickup.join() {
+            // off the map and into the pack
+            positions.remove(pickup.item);
+            backpack.insert(pickup.item, InBackpack{ owner: pickup.collected_by })
+                .expect("Unable to insert backpack entry");
+
+            if pickup.collected_by == *player_entity {
+                gamelog.entries.push(format!("You pick up the {}.",
+                                             names.get(pickup.item).unwrap().name));
+            }
+        }
+
+        wants_pickup.clear();
+    }
+}
+
+/// Applies whatever effects an item has, each given by a component on
+/// the item, to whoever it is used on: whoever uses it, or whatever is
+/// on the tile it was aimed at. Consumables are used up once they have
+/// done something, or once they have been aimed.
+pub struct ItemUseSystem {}
+
+impl<'a> System<'a> for ItemUseSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (ReadExpect<'a, Entity>,
+                       WriteExpect<'a, GameLog>,
+                       ReadExpect<'a, Map>,
+                       Entities<'a>,
+                       WriteStorage<'a, WantsToUseItem>,
+                       ReadStorage<'a, Name>,
+                       ReadStorage<'a, Consumable>,
+                       ReadStorage<'a, ProvidesHealing>,
+                       ReadStorage<'a, InflictsDamage>,
+                       WriteStorage<'a, CombatStats>,
+                       WriteStorage<'a, SufferDamage>,
+                       ReadStorage<'a, AreaOfEffect>,
+                       WriteStorage<'a, Confusion>,
+                       ReadStorage<'a, Monster>);
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity,
+             mut gamelog,
+             map,
+             entities,
+             mut wants_use,
+             names,
+             consumables,
+             healing,
+             inflict_damage,
+             mut combat_stats,
+             mut suffer_damage,
+             aoe,
+             mut confused,
+             monsters) = data;
+
+        for (entity, useitem) in (&entities, &wants_use).join() {
+            let item_name = &names.get(useitem.item).unwrap().name;
+            let mut affected = false;
+
+            // an item with a target is used on whatever is there, or
+            // everywhere the blast reaches, and anything else on whoever
+            // uses it
+            let mut targets: Vec<Entity> = Vec::new();
+
+            match (useitem.target, aoe.get(useitem.item)) {
+                (None, _) => targets.push(entity),
+                (Some(target), None) => {
+                    targets.extend(map.tile_content[map.xy_idx(target.x, target.y)].iter());
+                }
+                (Some(target), Some(area_effect)) => {
+                    let blast_tiles = rltk::field_of_view(target, area_effect.radius, &*map);
+
+                    for tile in blast_tiles.iter() {
+                        let in_bounds = tile.x > 0 && tile.x < map.width - 1 &&
+                                        tile.y > 0 && tile.y < map.height - 1;
+
+                        // the blast stops at walls, rather than on them
+                        if in_bounds && map.has_line_of_fire(target, *tile) {
+                            targets.extend(map.tile_content[map.xy_idx(tile.x, tile.y)].iter());
+                        }
+                    }
+                }
+            }
+
+            if let Some(healer) = healing.get(useitem.item) {
+                for target in targets.iter() {
+                    if let Some(stats) = combat_stats.get_mut(*target) {
+                        let healed = i32::min(stats.max_hp - stats.hp, healer.heal_amount);
+                        stats.hp += healed;
+                        affected = true;
+
+                        if entity == *player_entity {
+                            gamelog.entries.push(format!("You use the {}, healing {} hp.",
+                                                         item_name, healed));
+                        }
+                    }
+                }
+            }
+
+            if let Some(damage) = inflict_damage.get(useitem.item) {
+                for target in targets.iter() {
+                    if combat_stats.contains(*target) {
+                        SufferDamage::new_damage(&mut suffer_damage, *target, damage.damage);
+                        affected = true;
+
+                        if entity == *player_entity {
+                            let target_name = &names.get(*target).unwrap().name;
+                            gamelog.entries.push(format!("You use the {} on {}, inflicting {} hp.",
+                                                         item_name, target_name, damage.damage));
+                        }
+                    }
+                }
+            }
+
+            let mut add_confusion: Vec<(Entity, Confusion)> = Vec::new();
+
+            if let Some(confusion) = confused.get(useitem.item) {
+                for target in targets.iter().filter(|target| monsters.contains(**target)) {
+                    add_confusion.push((*target, confusion.clone()));
+                    affected = true;
+
+                    if entity == *player_entity {
+                        let target_name = &names.get(*target).unwrap().name;
+                        gamelog.entries.push(format!("You use the {} on {}, confusing them.",
+                                                     item_name, target_name));
+                    }
+                }
+            }
+
+            for (target, confusion) in add_confusion {
+                confused.insert(target, confusion).expect("Unable to insert status");
+            }
+
+            if !affected && entity == *player_entity {
+                gamelog.entries.push(format!("Nothing happens when you use the {}.", item_name));
+            }
+
+            // aiming has already taken the turn, so an aimed item is
+            // spent whether or not anything was there to catch it
+            let used = affected || useitem.target.is_some();
+
+            if used && consumables.contains(useitem.item) {
+                entities.delete(useitem.item).expect("Delete failed");
+            }
+        }
+
+        wants_use.clear();
+    }
+}
+
+pub struct ItemDropSystem {}
+
+impl<'a> System<'a> for ItemDropSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (ReadExpect<'a, Entity>,
+                       WriteExpect<'a, GameLog>,
+                       Entities<'a>,
+                       WriteStorage<'a, WantsToDropItem>,
+                       ReadStorage<'a, Name>,
+                       WriteStorage<'a, Position>,
+                       WriteStorage<'a, InBackpack>);
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity,
+             mut gamelog,
+             entities,
+             mut wants_drop,
+             names,
+             mut positions,
+             mut backpack) = data;
+
+        for (entity, to_drop) in (&entities, &wants_drop).join() {
+            // back on the map, under whoever dropped it
+            let dropper_pos = *positions.get(entity).unwrap();
+            positions.insert(to_drop.item, dropper_pos)
+                .expect("Unable to insert position");
+            backpack.remove(to_drop.item);
+
+            if entity == *player_entity {
+                gamelog.entries.push(format!("You drop the {}.",
+                                             names.get(to_drop.item).unwrap().name));
+            }
+        }
+
+        wants_drop.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{ Item, Monster, TileType };
+
+    use rltk::Point;
+
+    const TEST_WIDTH: i32 = 20;
+    const TEST_HEIGHT: i32 = 20;
+
+    /// A walled room of floor, with a player who has taken a few hits.
+    fn test_world() -> World {
+        let mut ecs = World::new();
+        ecs.register::<Name>();
+        ecs.register::<Item>();
+        ecs.register::<Monster>();
+        ecs.register::<Consumable>();
+        ecs.register::<ProvidesHealing>();
+        ecs.register::<InflictsDamage>();
+        ecs.register::<AreaOfEffect>();
+        ecs.register::<Confusion>();
+        ecs.register::<CombatStats>();
+        ecs.register::<SufferDamage>();
+        ecs.register::<WantsToUseItem>();
+
+        let mut map = Map::new(1, TEST_WIDTH, TEST_HEIGHT);
+
+        for y in 1..TEST_HEIGHT - 1 {
+            for x in 1..TEST_WIDTH - 1 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::Floor;
+            }
+        }
+
+        let player = ecs.create_entity()
+            .with(Name{ name: "Player".to_string() })
+            .with(CombatStats{ max_hp: 30, hp: 25, defense: 2, power: 5 })
+            .build();
+
+        ecs.insert(map);
+        ecs.insert(player);
+        ecs.insert(GameLog{ entries: Vec::new() });
+        ecs
+    }
+
+    /// A goblin standing at the given tile, indexed on the map.
+    fn add_monster(ecs: &mut World, x: i32, y: i32) -> Entity {
+        let monster = ecs.create_entity()
+            .with(Name{ name: "Goblin".to_string() })
+            .with(Monster{})
+            .with(CombatStats{ max_hp: 16, hp: 16, defense: 1, power: 4 })
+            .build();
+
+        let mut map = ecs.fetch_mut::<Map>();
+        let idx = map.xy_idx(x, y);
+        map.tile_content[idx].push(monster);
+        monster
+    }
+
+    fn use_item(ecs: &mut World, item: Entity, target: Option<Point>) {
+        let player = *ecs.fetch::<Entity>();
+        ecs.write_storage::<WantsToUseItem>()
+            .insert(player, WantsToUseItem{ item, target })
+            .expect("Unable to insert intent");
+
+        let mut item_use = ItemUseSystem{};
+        item_use.run_now(ecs);
+        ecs.maintain();
+    }
+
+    fn damage_taken(ecs: &World, entity: Entity) -> i32 {
+        ecs.read_storage::<SufferDamage>().get(entity).map_or(0, |damage| damage.amount.iter().sum())
+    }
+
+    #[test]
+    fn healing_stops_at_max_hp() {
+        let mut ecs = test_world();
+        let potion = ecs.create_entity()
+            .with(Name{ name: "Health Potion".to_string() })
+            .with(Item{})
+            .with(Consumable{})
+            .with(ProvidesHealing{ heal_amount: 8 })
+            .build();
+
+        use_item(&mut ecs, potion, None);
+
+        let player = *ecs.fetch::<Entity>();
+        assert_eq!(ecs.read_storage::<CombatStats>().get(player).unwrap().hp, 30);
+    }
+
+    #[test]
+    fn consumables_are_used_up() {
+        let mut ecs = test_world();
+        let potion = ecs.create_entity()
+            .with(Name{ name: "Health Potion".to_string() })
+            .with(Item{})
+            .with(Consumable{})
+            .with(ProvidesHealing{ heal_amount: 8 })
+            .build();
+        let charm = ecs.create_entity()
+            .with(Name{ name: "Healing Charm".to_string() })
+            .with(Item{})
+            .with(ProvidesHealing{ heal_amount: 1 })
+            .build();
+
+        use_item(&mut ecs, potion, None);
+        use_item(&mut ecs, charm, None);
+
+        assert!(!ecs.is_alive(potion));
+        assert!(ecs.is_alive(charm));
+    }
+
+    #[test]
+    fn aimed_items_are_used_up_on_an_empty_tile() {
+        let mut ecs = test_world();
+        let scroll = ecs.create_entity()
+            .with(Name{ name: "Magic Missile Scroll".to_string() })
+            .with(Item{})
+            .with(Consumable{})
+            .with(InflictsDamage{ damage: 8 })
+            .build();
+
+        use_item(&mut ecs, scroll, Some(Point::new(5, 5)));
+
+        assert!(!ecs.is_alive(scroll));
+    }
+
+    #[test]
+    fn fireball_hits_everything_in_the_blast() {
+        let mut ecs = test_world();
+        let near = add_monster(&mut ecs, 10, 10);
+        let edge = add_monster(&mut ecs, 12, 10);
+        let outside = add_monster(&mut ecs, 15, 10);
+        let scroll = ecs.create_entity()
+            .with(Name{ name: "Fireball Scroll".to_string() })
+            .with(Item{})
+            .with(Consumable{})
+            .with(InflictsDamage{ damage: 20 })
+            .with(AreaOfEffect{ radius: 3 })
+            .build();
+
+        use_item(&mut ecs, scroll, Some(Point::new(10, 10)));
+
+        assert_eq!(damage_taken(&ecs, near), 20);
+        assert_eq!(damage_taken(&ecs, edge), 20);
+        assert_eq!(damage_taken(&ecs, outside), 0);
+    }
+
+    #[test]
+    fn fireball_stops_at_walls() {
+        let mut ecs = test_world();
+
+        // a wall across the room, with the blast on one side of it
+        {
+            let mut map = ecs.fetch_mut::<Map>();
+
+            for y in 1..TEST_HEIGHT - 1 {
+                let idx = map.xy_idx(11, y);
+                map.tiles[idx] = TileType::Wall;
+            }
+        }
+
+        let in_front = add_monster(&mut ecs, 10, 10);
+        let behind = add_monster(&mut ecs, 12, 10);
+        let scroll = ecs.create_entity()
+            .with(Name{ name: "Fireball Scroll".to_string() })
+            .with(Item{})
+            .with(Consumable{})
+            .with(InflictsDamage{ damage: 20 })
+            .with(AreaOfEffect{ radius: 3 })
+            .build();
+
+        use_item(&mut ecs, scroll, Some(Point::new(10, 10)));
+
+        assert_eq!(damage_taken(&ecs, in_front), 20);
+        assert_eq!(damage_taken(&ecs, behind), 0);
+    }
+
+    #[test]
+    fn confusion_is_applied_to_monsters() {
+        let mut ecs = test_world();
+        let goblin = add_monster(&mut ecs, 8, 8);
+        let scroll = ecs.create_entity()
+            .with(Name{ name: "Confusion Scroll".to_string() })
+            .with(Item{})
+            .with(Consumable{})
+            .with(Confusion{ turns: 4 })
+            .build();
+
+        use_item(&mut ecs, scroll, Some(Point::new(8, 8)));
+
+        assert_eq!(ecs.read_storage::<Confusion>().get(goblin).map(|c| c.turns), Some(4));
+        assert!(!ecs.is_alive(scroll));
+    }
+}
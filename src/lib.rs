@@ -0,0 +1,10 @@
+// the map, and everything it takes to build one, shared by the game
+// and the headless map generator
+mod components;
+pub use components::*;
+mod map;
+pub use map::*;
+mod rect;
+pub use rect::Rect;
+pub mod spawner;
+pub mod map_builders;
@@ -1,11 +1,6 @@
-mod components;
-pub use components::*;
-mod map;
-pub use map::*;
+use map_making::*;
 mod player;
 pub use player::*;
-mod rect;
-pub use rect::Rect;
 mod visibility_system;
 pub use visibility_system::*;
 mod monster_ai_system;
@@ -16,37 +11,88 @@ mod melee_combat_system;
 pub use melee_combat_system::*;
 mod damage_system;
 pub use damage_system::*;
+mod inventory_system;
+pub use inventory_system::*;
 mod gui;
 mod gamelog;
 pub use gamelog::*;
+mod dungeon;
+pub use dungeon::MasterDungeonMap;
+mod camera;
 
-use rltk::{ GameState, Rltk, RGB, Point };
+use rltk::{ GameState, Rltk, Point, RandomNumberGenerator };
 use specs::prelude::*;
 
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const LEVEL_WIDTH: i32 = 120; // levels are bigger than the screen,
+const LEVEL_HEIGHT: i32 = 70; // and the camera follows the player
+
+const USAGE: &str = "\
+usage: map_making [options]
+
+  --seed <n>               seed for the whole dungeon (default random)
+  --level <depth>=<file>   play a hand-made level at that depth, as text or
+                           a REX Paint .xp file; can be given once per depth
+  --show-mapgen            watch each level being generated";
+
+const MAPGEN_FRAME_MS: f32 = 150.0; // how long each generation snapshot is shown,
+const MAPGEN_PLAYBACK_MS: f32 = 10000.0; // unless there are too many to fit in this
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum RunState {
     AwaitingInput,
     PreRun,
     PlayerTurn,
     MonsterTurn,
+    NextLevel,
+    PreviousLevel,
+    MapGeneration,
+    ShowInventory,
+    ShowDropItem,
+    ShowTargeting { range: i32, item: Entity },
 }
 
 pub struct State {
     pub ecs: World,
+    show_mapgen: bool,
+    mapgen_history: Vec<Vec<TileType>>,
+    mapgen_index: usize,
+    mapgen_timer: f32,
+    mapgen_next_state: RunState,
+    level_files: HashMap<i32, PathBuf>,
+    target_cursor: gui::TargetCursor,
 }
 
 impl State {
     fn run_systems(&mut self) {
         // run all the systems as necessary.
+        // the map is indexed before anyone looks around, so the doors
+        // on a level that was just built or thawed already block sight
+        let mut map_index = MapIndexingSystem{};
+        map_index.run_now(&self.ecs);
+
         let mut vis = VisibilitySystem{};
         vis.run_now(&self.ecs);
 
         let mut mob = MonsterAI{};
         mob.run_now(&self.ecs);
 
-        let mut map_index = MapIndexingSystem{};
+        // and again once the monsters have moved
         map_index.run_now(&self.ecs);
 
+        // items go before any damage is dealt, so whatever an item
+        // kills is dead before it gets another swing
+        let mut pickup = ItemCollectionSystem{};
+        pickup.run_now(&self.ecs);
+
+        let mut items = ItemUseSystem{};
+        items.run_now(&self.ecs);
+
+        let mut drop_items = ItemDropSystem{};
+        drop_items.run_now(&self.ecs);
+
         let mut melee = MeleeCombatSystem{};
         melee.run_now(&self.ecs);
 
@@ -55,6 +101,138 @@ impl State {
 
         self.ecs.maintain();
     }
+
+    /// Builds a level at the given depth, fills it with monsters and
+    /// moves the player to its starting position.
+    fn generate_world_map(&mut self, new_depth: i32) {
+        let mut builder;
+
+        {
+            let mut rng = self.ecs.write_resource::<RandomNumberGenerator>();
+            builder = match self.hand_made_level(new_depth, &mut rng) {
+                Some(builder) => builder,
+                None => map_builders::build_random_level(new_depth, LEVEL_WIDTH, LEVEL_HEIGHT,
+                                                         self.show_mapgen, &mut rng),
+            };
+        }
+
+        self.mapgen_history = std::mem::take(&mut builder.build_data.history.snapshots);
+        self.mapgen_index = 0;
+        self.mapgen_timer = 0.0;
+
+        // the spawner places entities by map index, so the map
+        // must be in the ECS before anything is created
+        self.ecs.insert(builder.build_data.map.clone());
+        builder.spawn_entities(&mut self.ecs);
+
+        self.place_player(builder.get_starting_position());
+    }
+
+    /// Builds the level given with `--level` for this depth, if there
+    /// is one. A level that can't be read or played is logged, and
+    /// a random one is built in its place.
+    fn hand_made_level(&self, new_depth: i32, rng: &mut RandomNumberGenerator)
+                       -> Option<map_builders::BuilderChain> {
+        let path = self.level_files.get(&new_depth)?;
+
+        let mut builder = match map_builders::level_from_file(path, new_depth,
+                                                              LEVEL_WIDTH, LEVEL_HEIGHT) {
+            Ok(builder) => builder,
+            Err(e) => {
+                rltk::console::log(format!("Can't use {}: {}", path.display(), e));
+                return None;
+            }
+        };
+
+        if self.show_mapgen {
+            builder.record_history();
+        }
+
+        builder.build_map(rng);
+
+        if !builder.validate() {
+            rltk::console::log(format!("Can't use {}: it needs a way down and more room to explore",
+                                       path.display()));
+            return None;
+        }
+
+        Some(builder)
+    }
+
+    /// Plays back how the level was built before going on to `next`,
+    /// if a new level was built while its history was being kept.
+    fn play_mapgen_before(&mut self, next: RunState) -> RunState {
+        if self.mapgen_history.is_empty() {
+            return next;
+        }
+
+        self.mapgen_next_state = next;
+        RunState::MapGeneration
+    }
+
+    fn place_player(&mut self, player_start: Position) {
+        let mut player_point = self.ecs.write_resource::<Point>();
+        *player_point = Point::new(player_start.x, player_start.y);
+
+        let player_entity = self.ecs.fetch::<Entity>();
+        let mut positions = self.ecs.write_storage::<Position>();
+
+        if let Some(pos) = positions.get_mut(*player_entity) {
+            *pos = player_start;
+        }
+
+        // the player is looking at a whole new level
+        let mut viewsheds = self.ecs.write_storage::<Viewshed>();
+
+        if let Some(viewshed) = viewsheds.get_mut(*player_entity) {
+            viewshed.dirty = true;
+        }
+    }
+
+    /// Moves the player `offset` levels down the dungeon (or up, if
+    /// negative). The level being left is frozen as it is; a level
+    /// the player has been to before is thawed rather than rebuilt.
+    fn goto_level(&mut self, offset: i32) {
+        dungeon::freeze_level_entities(&mut self.ecs);
+
+        let new_depth;
+
+        {
+            let map = self.ecs.fetch::<Map>();
+            let mut dungeon_master = self.ecs.write_resource::<MasterDungeonMap>();
+            dungeon_master.store_map(&map);
+            new_depth = map.depth + offset;
+        }
+
+        let stored_map = self.ecs.fetch::<MasterDungeonMap>().get_map(new_depth);
+
+        match stored_map {
+            None => self.generate_world_map(new_depth),
+            Some(map) => {
+                // arrive on the stairs leading back the way we came
+                let arrival = if offset > 0 { TileType::UpStairs } else { TileType::DownStairs };
+                let arrival_idx = map.tiles.iter()
+                    .position(|tile| *tile == arrival)
+                    .expect("Stored level has no stairs to arrive on");
+                let arrival_pos = Position{
+                    x: arrival_idx as i32 % map.width,
+                    y: arrival_idx as i32 / map.width,
+                };
+
+                self.ecs.insert(map);
+                dungeon::thaw_level_entities(&mut self.ecs);
+                self.place_player(arrival_pos);
+            }
+        }
+
+        let mut log = self.ecs.fetch_mut::<GameLog>();
+
+        if offset > 0 {
+            log.entries.push("You descend to the next level.".to_string());
+        } else {
+            log.entries.push("You climb back up the stairs.".to_string());
+        }
+    }
 }
 
 impl GameState for State {
@@ -69,6 +247,13 @@ impl GameState for State {
             new_run_state = *run_state;
         }
 
+        // the playback has the screen to itself; otherwise the level is
+        // drawn first, so menus can go over the top of it
+        if new_run_state != RunState::MapGeneration {
+            camera::render_camera(&self.ecs, ctx);
+            gui::draw_ui(&self.ecs, ctx);
+        }
+
         match new_run_state {
             RunState::PreRun => {
                 self.run_systems();
@@ -88,6 +273,96 @@ impl GameState for State {
                 self.run_systems();
                 new_run_state = RunState::AwaitingInput;
             }
+
+            RunState::NextLevel => {
+                self.goto_level(1);
+                new_run_state = self.play_mapgen_before(RunState::PreRun);
+            }
+
+            RunState::PreviousLevel => {
+                self.goto_level(-1);
+                new_run_state = self.play_mapgen_before(RunState::PreRun);
+            }
+
+            RunState::ShowInventory => {
+                let (result, item) = gui::show_inventory(&self.ecs, ctx, "Inventory");
+
+                match result {
+                    gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item = item.unwrap();
+                        let range = self.ecs.read_storage::<Ranged>().get(item).map(|ranged| ranged.range);
+
+                        match range {
+                            Some(range) => {
+                                self.target_cursor = gui::TargetCursor::new(ctx.mouse_pos());
+                                new_run_state = RunState::ShowTargeting{ range, item };
+                            }
+                            None => {
+                                let player_entity = *self.ecs.fetch::<Entity>();
+                                let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                                intent.insert(player_entity, WantsToUseItem{ item, target: None })
+                                    .expect("Unable to insert intent");
+                                new_run_state = RunState::PlayerTurn;
+                            }
+                        }
+                    }
+                }
+            }
+
+            RunState::ShowTargeting{ range, item } => {
+                let (result, target) = gui::ranged_target(&self.ecs, ctx, range, &mut self.target_cursor);
+
+                match result {
+                    gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let player_entity = *self.ecs.fetch::<Entity>();
+                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                        intent.insert(player_entity, WantsToUseItem{ item, target })
+                            .expect("Unable to insert intent");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+
+            RunState::ShowDropItem => {
+                let (result, item) = gui::show_inventory(&self.ecs, ctx, "Drop Which Item?");
+
+                match result {
+                    gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let player_entity = *self.ecs.fetch::<Entity>();
+                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                        intent.insert(player_entity, WantsToDropItem{ item: item.unwrap() })
+                            .expect("Unable to insert intent");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+
+            RunState::MapGeneration => {
+                let snapshots = self.mapgen_history.len();
+                camera::render_snapshot(&self.ecs, &self.mapgen_history[self.mapgen_index], ctx);
+                gui::draw_mapgen_progress(ctx, self.mapgen_index + 1, snapshots);
+
+                // long histories are sped up to fit the playback time
+                let frame_ms = MAPGEN_FRAME_MS.min(MAPGEN_PLAYBACK_MS / snapshots as f32);
+                self.mapgen_timer += ctx.frame_time_ms;
+
+                if self.mapgen_timer > frame_ms {
+                    self.mapgen_timer = 0.0;
+                    self.mapgen_index += 1;
+                }
+
+                // any key skips to the end
+                if ctx.key.is_some() || self.mapgen_index >= snapshots {
+                    self.mapgen_history.clear();
+                    new_run_state = self.mapgen_next_state;
+                }
+            }
         }
 
         {
@@ -96,32 +371,70 @@ impl GameState for State {
         }
 
         damage_system::delete_the_dead(&mut self.ecs);
-        draw_map(&self.ecs, ctx);
+    }
+}
 
-        let positions = self.ecs.read_storage::<Position>();
-        let renderables = self.ecs.read_storage::<Renderable>();
-        let map = self.ecs.fetch::<Map>();
+/// Reads the seed from `--seed <number>` on the command line. Without
+/// one a new seed is rolled, which is still logged so the run can be
+/// reproduced later.
+fn dungeon_seed() -> Result<u64, String> {
+    let args: Vec<String> = std::env::args().collect();
+
+    match args.iter().position(|arg| arg == "--seed") {
+        Some(i) => args.get(i + 1)
+            .and_then(|seed| seed.parse().ok())
+            .ok_or_else(|| "--seed needs a whole number, such as --seed 1234".to_string()),
+        None => Ok(RandomNumberGenerator::new().next_u64()),
+    }
+}
 
-        for (pos, render) in (&positions, &renderables).join() {
-            let idx = map.xy_idx(pos.x, pos.y);
+/// Reads hand-made levels from `--level <depth>=<file>`, which can be
+/// given once for each depth. Depths without one are built at random.
+fn level_files() -> Result<HashMap<i32, PathBuf>, String> {
+    let args: Vec<String> = std::env::args().collect();
+    let mut files = HashMap::new();
+
+    for (i, _) in args.iter().enumerate().filter(|(_, arg)| *arg == "--level") {
+        let (depth, path) = args.get(i + 1)
+            .and_then(|value| value.split_once('='))
+            .and_then(|(depth, path)| Some((depth.parse::<i32>().ok()?, path)))
+            .filter(|(depth, path)| *depth >= 1 && !path.is_empty())
+            .ok_or_else(|| "--level needs a depth from 1 and a file, such as --level 1=levels/start.xp"
+                           .to_string())?;
+
+        files.insert(depth, PathBuf::from(path));
+    }
 
-            if map.visible_tiles[idx] {
-                ctx.set(pos.x, pos.y, render.fg, render.bg, render.glyph);
-            }
-        }
+    Ok(files)
+}
 
-        gui::draw_ui(&self.ecs, ctx);
-    }
+/// Gives up before anything is built, for a command line that can't be
+/// used.
+fn bad_arguments(message: String) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    std::process::exit(2);
 }
 
 fn main() -> rltk::BError {
     use rltk::RltkBuilder;
 
+    let seed = dungeon_seed().unwrap_or_else(|message| bad_arguments(message));
+    let level_files = level_files().unwrap_or_else(|message| bad_arguments(message));
+
     let context = RltkBuilder::simple80x50()
                     .with_title("Roguelike Tutorial")
                     .build()?;
 
-    let mut gs = State { ecs: World::new() };
+    let mut gs = State {
+        ecs: World::new(),
+        show_mapgen: std::env::args().any(|arg| arg == "--show-mapgen"),
+        mapgen_history: Vec::new(),
+        mapgen_index: 0,
+        mapgen_timer: 0.0,
+        mapgen_next_state: RunState::PreRun,
+        level_files,
+        target_cursor: gui::TargetCursor::new((0, 0)),
+    };
 
     // Component Registrations
     gs.ecs.register::<Position>();
@@ -131,88 +444,43 @@ fn main() -> rltk::BError {
     gs.ecs.register::<Monster>();
     gs.ecs.register::<Name>();
     gs.ecs.register::<BlocksTile>();
+    gs.ecs.register::<Door>();
     gs.ecs.register::<CombatStats>();
     gs.ecs.register::<WantsToMelee>();
     gs.ecs.register::<SufferDamage>();
-
-    let map: Map = Map::new_map_rooms_and_corridors();
-    let (player_x, player_y) = map.rooms[0].center();
-    let mut rng = rltk::RandomNumberGenerator::new();
-
-    // populate the dungeon with MONSTERS!
-    // g - Goblin
-    // o - for WE DA ORKS!
-    for (i, room) in map.rooms.iter().skip(1).enumerate() {
-        let (x, y) = room.center();
-
-        let glyph: rltk::FontCharType;
-        let name: String;
-        let roll = rng.roll_dice(1, 2);
-
-        match roll {
-            1 => { 
-                glyph = rltk::to_cp437('g');
-                name = "Goblin".to_string();
-            },
-            _ => { 
-                glyph = rltk::to_cp437('o');
-                name = "Ork".to_string();
-            },
-        }
-
-        gs.ecs.create_entity()
-                .with(Position{ x, y})
-                .with(Renderable{
-                    glyph: glyph,
-                    fg: RGB::named(rltk::RED),
-                    bg: RGB::named(rltk::BLACK)
-                })
-                .with(Viewshed{
-                    visible_tiles: Vec::new(),
-                    range: 8,
-                    dirty: true
-                })
-                .with(Monster{})
-                .with(Name{ name: format!("{} #{}", &name, i)})
-                .with(BlocksTile{})
-                .with(CombatStats {
-                    max_hp: 16,
-                    hp: 16,
-                    defense: 2,
-                    power: 5,
-                })
-                .build();
-    }
-
-    let player_entity = gs.ecs
-        .create_entity()
-        .with(Position{ x: player_x, y: player_y })
-        .with(Renderable {
-            glyph: rltk::to_cp437('@'),
-            fg: RGB::named(rltk::YELLOW),
-            bg: RGB::named(rltk::BLACK),
-        })
-        .with(Player{})
-        .with(Viewshed{ 
-            visible_tiles: Vec::new(), 
-            range: 8,
-            dirty: true,
-        })
-        .with(Name{ name: "Player".to_string() })
-        .with(CombatStats{
-            max_hp: 30,
-            hp: 30,
-            defense: 2,
-            power: 5
-        })
-        .build();
-
-    gs.ecs.insert(map);
-    gs.ecs.insert(Point::new(player_x, player_x));
+    gs.ecs.register::<OtherLevelPosition>();
+    gs.ecs.register::<Item>();
+    gs.ecs.register::<InBackpack>();
+    gs.ecs.register::<WantsToPickupItem>();
+    gs.ecs.register::<WantsToUseItem>();
+    gs.ecs.register::<WantsToDropItem>();
+    gs.ecs.register::<Consumable>();
+    gs.ecs.register::<ProvidesHealing>();
+    gs.ecs.register::<InflictsDamage>();
+    gs.ecs.register::<Ranged>();
+    gs.ecs.register::<AreaOfEffect>();
+    gs.ecs.register::<Confusion>();
+
+    // every random roll in the game comes from this one generator,
+    // so a run can be replayed from its seed.
+    rltk::console::log(format!("Dungeon seed: {}", seed));
+    gs.ecs.insert(RandomNumberGenerator::seeded(seed));
+
+    // the player is created first and placed once the
+    // first level has been built
+    let player_entity = spawner::player(&mut gs.ecs, 0, 0);
+    gs.ecs.insert(Point::new(0, 0));
     gs.ecs.insert(player_entity);
-    gs.ecs.insert(RunState::PreRun);
+    gs.ecs.insert(MasterDungeonMap::new());
+    gs.generate_world_map(1);
+
+    let first_state = gs.play_mapgen_before(RunState::PreRun);
+    gs.ecs.insert(first_state);
     gs.ecs.insert(gamelog::GameLog{
-        entries: vec!["Welcome to Rusty Roguelike.".to_string()]
+        entries: vec![
+            "Welcome to Rusty Roguelike.".to_string(),
+            format!("Dungeon seed: {}", seed),
+        ]
     });
 
     rltk::main_loop(context, gs)
@@ -1,25 +1,67 @@
-use super::{Viewshed, Player};
 use super::{ Rect };
 
-use rltk::{ RGB, Rltk, RandomNumberGenerator, 
-            Algorithm2D, BaseMap, Point 
-        };
+use rltk::{ Algorithm2D, BaseMap, Point };
 
 use specs::prelude::*;
 
-use std::cmp::{ max, min };
-
-// constants for map
-const MAPWIDTH: usize = 80;
-const MAPHEIGHT: usize = 43;
-const MAPCOUNT: usize = MAPWIDTH * MAPHEIGHT;
-
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum TileType {
     Wall,
     Floor,
+    DownStairs,
+    UpStairs,
+    ShallowWater,
+    DeepWater,
+    Lava,
+    Grass,
+    Bridge,
+    Rubble,
+    GlassWall,
+}
+
+/// True if something can stand on the tile.
+pub fn tile_walkable(tt: TileType) -> bool {
+    matches!(tt, TileType::Floor | TileType::DownStairs | TileType::UpStairs |
+                 TileType::ShallowWater | TileType::Grass | TileType::Bridge |
+                 TileType::Rubble)
+}
+
+/// True if the tile blocks line of sight. Glass walls stop you, but
+/// not your eyes.
+pub fn tile_opaque(tt: TileType) -> bool {
+    tt == TileType::Wall
+}
+
+/// How much it costs to step onto the tile, relative to bare floor.
+/// Pathing prefers the cheaper way round where there is one.
+pub fn tile_cost(tt: TileType) -> f32 {
+    match tt {
+        TileType::ShallowWater => 2.0,
+        TileType::Rubble => 1.5,
+        TileType::Grass => 1.1,
+        _ => 1.0,
+    }
+}
+
+/// The character a tile is written as in a text dump of the map. These
+/// match the prefab templates, so a dump can be drawn back in as one.
+pub fn tile_char(tt: TileType) -> char {
+    match tt {
+        TileType::Wall => '#',
+        TileType::Floor => '.',
+        TileType::DownStairs => '>',
+        TileType::UpStairs => '<',
+        TileType::ShallowWater => '~',
+        TileType::DeepWater => 'W',
+        TileType::Lava => 'L',
+        TileType::Grass => '"',
+        TileType::Bridge => '=',
+        TileType::Rubble => ';',
+        TileType::GlassWall => 'G',
+    }
 }
 
+#[derive(Clone)]
 pub struct Map {
     pub tiles: Vec<TileType>,
     pub rooms: Vec<Rect>,
@@ -28,10 +70,13 @@ pub struct Map {
     pub revealed_tiles: Vec<bool>,
     pub visible_tiles: Vec<bool>,
     pub blocked: Vec<bool>,
+    pub closed_doors: Vec<bool>,
     pub tile_content: Vec<Vec<Entity>>,
+    pub depth: i32,
 }
 
 // trait impls
+
 impl Algorithm2D for Map {
     fn dimensions(&self) -> rltk::Point {
         Point::new(self.width, self.height)
@@ -40,7 +85,7 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx as usize] == TileType::Wall
+        tile_opaque(self.tiles[idx]) || self.closed_doors[idx]
     }
 
     fn get_available_exits(&self, idx: usize) -> 
@@ -49,39 +94,40 @@ impl BaseMap for Map {
         let x = idx as i32 % self.width;
         let y = idx as i32 / self.width;
         let w = self.width as usize;
+        let cost = |dest: usize| tile_cost(self.tiles[dest]);
 
         // cardinal directions
         if self.is_exit_valid(x - 1, y) {
-            exits.push((idx - 1, 1.0));
+            exits.push((idx - 1, cost(idx - 1)));
         }
 
         if self.is_exit_valid(x + 1, y) {
-            exits.push((idx + 1, 1.0));
+            exits.push((idx + 1, cost(idx + 1)));
         }
 
         if self.is_exit_valid(x, y - 1) {
-            exits.push((idx - 1, 1.0));
+            exits.push((idx - w, cost(idx - w)));
         }
 
         if self.is_exit_valid(x, y + 1) {
-            exits.push((idx + w, 1.0));
+            exits.push((idx + w, cost(idx + w)));
         }
 
         // diagonals
         if self.is_exit_valid(x-1, y-1) {
-            exits.push(((idx-w)-1, 1.45));
+            exits.push(((idx-w)-1, cost((idx-w)-1) * 1.45));
         }
 
         if self.is_exit_valid(x+1, y-1) {
-            exits.push(((idx-w)+1, 1.45));
+            exits.push(((idx-w)+1, cost((idx-w)+1) * 1.45));
         }
 
         if self.is_exit_valid(x-1, y+1) {
-            exits.push(((idx+w)-1, 1.45));
+            exits.push(((idx+w)-1, cost((idx+w)-1) * 1.45));
         }
 
         if self.is_exit_valid(x+1, y+1) {
-            exits.push(((idx+w)+1, 1.45));
+            exits.push(((idx+w)+1, cost((idx+w)+1) * 1.45));
         }
 
         exits
@@ -96,6 +142,26 @@ impl BaseMap for Map {
 }
 
 impl Map {
+    /// Creates a map of solid wall; the map builders carve it out.
+    /// Depth 1 is the top level of the dungeon. Maps can be any size,
+    /// since the camera only ever draws the part around the player.
+    pub fn new(new_depth: i32, width: i32, height: i32) -> Map {
+        let map_tile_count = (width * height) as usize;
+
+        Map {
+            tiles: vec![TileType::Wall; map_tile_count],
+            rooms: Vec::new(),
+            width,
+            height,
+            revealed_tiles: vec![false; map_tile_count],
+            visible_tiles: vec![false; map_tile_count],
+            blocked: vec![false; map_tile_count],
+            closed_doors: vec![false; map_tile_count],
+            tile_content: vec![Vec::new(); map_tile_count],
+            depth: new_depth,
+        }
+    }
+
     pub fn xy_idx(&self, x: i32, y: i32) -> usize {
         (y as usize * self.width as usize) + x as usize
     }
@@ -107,127 +173,28 @@ impl Map {
         if x < 1 || x > self.width - 1 ||
            y < 1 || y > self.height - 1 { return false; }
 
-        // if the position is a Wall Tile, then it is also not 
-        // a valid exit.
+        // if the position can't be walked on, then it is also not 
+        // a valid exit. A closed door still is, since whoever
+        // gets there can open it.
         let idx = self.xy_idx(x, y);
-        !self.blocked[idx]
+        !self.blocked[idx] || self.closed_doors[idx]
     }
 
-    fn apply_room_to_map(&mut self, room: &Rect) {
-        // inclusive range
-        // we want to visit every segment in the rooms rectangle
-        // and tile it with the Floor tile
-        // 
-        // this needs to be done for both y and x.
-        for y in room.y1 + 1 ..= room.y2 {
-            for x in room.x1 + 1 ..= room.x2 {
-                let idx = self.xy_idx(x, y);
-                self.tiles[idx] = TileType::Floor;
-            }
-        }
-    }
-    
-    fn apply_horizontal_tunnel(&mut self,x1: i32, x2: i32, y: i32) {
-        for x in min(x1, x2) ..= max(x1, x2) {
-            let idx = self.xy_idx(x, y);
-    
-            if idx > 0 && idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
-            }
-        }
-    }
-    
-    fn apply_vertical_tunnel(&mut self, y1: i32, y2: i32, x: i32) {
-        for y in min(y1, y2) ..= max (y1, y2) {
-            let idx = self.xy_idx(x, y);
-    
-            if idx > 0 && idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
-            }
-        }
-    }
-
-    pub fn new_map_rooms_and_corridors() -> Map {
-        let mut map = Map {
-            tiles: vec![TileType::Wall; MAPCOUNT],
-            rooms: Vec::new(),
-            width: MAPWIDTH as i32,
-            height: MAPHEIGHT as i32,
-            revealed_tiles: vec![false; MAPCOUNT],
-            visible_tiles: vec![false; MAPCOUNT],
-            blocked: vec![false; MAPCOUNT],
-            tile_content: vec![Vec::new(); MAPCOUNT],
-        };
-    
-        const MAX_ROOMS: i32 = 30; // maximum number of rooms possible
-        const MIN_SIZE: i32 = 6; // minimum room size in tiles
-        const MAX_SIZE: i32 = 10; // maximum room size in tiles
-    
-        let mut rng = RandomNumberGenerator::new();
-    
-        for _ in 0..MAX_ROOMS {
-            // generate a width/height for a room by obtaining
-            // a value between the rang of MIN_SIZE and MAX_SIZE
-            // using the Rltk random number generator
-            let w = rng.range(MIN_SIZE, MAX_SIZE);
-            let h = rng.range(MIN_SIZE, MAX_SIZE);
-    
-            // generate the x and y size of of the rectangle
-            // in this case, we're obtaining a value between:
-            //
-            //  x = 1 -> the screen width minus the generated width of the 
-            //           the rectangle.
-            //
-            //  y = 1 -> the screen height minus the generated height of 
-            //           the rectangle
-            let x = rng.roll_dice(1, map.width - w - 1) - 1;
-            let y = rng.roll_dice(1, map.height - h - 1) - 1;
-            let new_room = Rect::new(x, y, w, h);
-            let mut ok = true;
-    
-            // check to verify that the new room DOES NOT intersect
-            // in anyway the other rooms that have previously been generated
-            // due to the randomness, we are guaranteeded AT LEAST one room
-            // and AT MOST the maximum rooms
-            for other_room in map.rooms.iter() {
-                if new_room.intersect(other_room) {
-                    ok = false;
-                }
-            }
-    
-            if ok {
-                // bind the new room to the map
-                map.apply_room_to_map(&new_room);
-    
-                // if there is more than one room in the vector of Rectangles.
-                if !map.rooms.is_empty() {
-                    // obtain the center of the new room
-                    let (new_x, new_y) = new_room.center();
-    
-                    // obtain the center of the last added room
-                    let room_len = map.rooms.len() - 1;
-                    let (prev_x, prev_y) = map.rooms[room_len].center();
-    
-                    // randomly decide how the tunnels should be connected
-                    if rng.range(0, 2) == 1 {
-                        map.apply_horizontal_tunnel(prev_x, new_x, prev_y);
-                        map.apply_vertical_tunnel(prev_y, new_y, new_x);
-                    } else {
-                        map.apply_vertical_tunnel(prev_y, new_y, prev_x);
-                        map.apply_horizontal_tunnel(prev_x, new_x, new_y);
-                    }
-                }
-            }
-    
-            map.rooms.push(new_room);
-        }
-    
-        map
+    /// True if nothing solid stands between two tiles, or on the far
+    /// one, so something can be thrown or cast from one to the other.
+    /// Glass walls can be seen through, but not shot through.
+    pub fn has_line_of_fire(&self, from: Point, to: Point) -> bool {
+        rltk::line2d(rltk::LineAlg::Bresenham, from, to).iter()
+            .filter(|pt| **pt != from)
+            .all(|pt| {
+                let idx = self.xy_idx(pt.x, pt.y);
+                !self.is_opaque(idx) && self.tiles[idx] != TileType::GlassWall
+            })
     }
 
     pub fn populate_blocked(&mut self) {
         for (i, tile) in self.tiles.iter_mut().enumerate() {
-            self.blocked[i] = *tile == TileType::Wall;
+            self.blocked[i] = !tile_walkable(*tile);
         }
     }
 
@@ -235,44 +202,16 @@ impl Map {
         for content in self.tile_content.iter_mut() {
             content.clear();
         }
-    }
-}
 
-pub fn draw_map(ecs: &World, ctx: &mut Rltk) {
-    let map = ecs.fetch::<Map>();
-    let mut x = 0;
-    let mut y = 0;
-
-    for (idx, tile) in map.tiles.iter().enumerate() {
-        if map.revealed_tiles[idx] {
-            let glyph;
-            let mut fg;
-
-            match tile {
-                TileType::Floor => {
-                    glyph = rltk::to_cp437('.');
-                    fg = RGB::from_f32(0.0, 0.5, 0.5);
-                }
-    
-                TileType::Wall => {
-                    glyph = rltk::to_cp437('#');
-                    fg = RGB::from_f32(0.0, 1.0, 0.0);
-                }
-            }
-            
-            if !map.visible_tiles[idx] {
-                fg = fg.to_greyscale();
-            }
-
-            ctx.set(x, y, fg, RGB::from_f32(0., 0., 0.), glyph);
+        for door in self.closed_doors.iter_mut() {
+            *door = false;
         }
-        
-        // move the coordinates a bit...
-        x += 1;
+    }
 
-        if x > MAPWIDTH as i32 - 1 {
-            x = 0;
-            y += 1;
-        }
+    /// Marks a door as open on the map right away, rather than
+    /// waiting for the next indexing pass.
+    pub fn open_door(&mut self, idx: usize) {
+        self.closed_doors[idx] = false;
+        self.blocked[idx] = false;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,112 @@
+use super::{ InitialMapBuilder, BuilderMap, MapHistory, Map, Rect,
+             apply_room_to_map, apply_horizontal_tunnel,
+             apply_vertical_tunnel };
+
+use rltk::RandomNumberGenerator;
+
+const MIN_LEAF_SIZE: i32 = 8; // a leaf is never split below this size
+const MIN_ROOM_SIZE: i32 = 4; // smallest room carved inside a leaf
+
+pub struct BspDungeonBuilder {}
+
+impl InitialMapBuilder for BspDungeonBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, &mut build_data.map, &mut build_data.history);
+    }
+}
+
+impl BspDungeonBuilder {
+    pub fn new() -> Box<BspDungeonBuilder> {
+        Box::new(BspDungeonBuilder {})
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, map: &mut Map, history: &mut MapHistory) {
+        // the whole map is the root of the tree; the outermost
+        // row and column stay as walls because rooms are carved
+        // one tile inside their rectangle.
+        let root = Rect::new(0, 0, map.width - 1, map.height - 1);
+        self.partition(rng, map, history, &root);
+    }
+
+    /// Recursively splits `leaf` in two until it is too small to split
+    /// again, then carves a room in it. Each pair of siblings is joined
+    /// by a corridor on the way back up the tree.
+    ///
+    /// Returns the center of one room in this subtree, which the parent
+    /// uses as the end point of its connecting corridor.
+    fn partition(&mut self, rng: &mut RandomNumberGenerator, map: &mut Map,
+                 history: &mut MapHistory, leaf: &Rect) -> (i32, i32) {
+        let width = leaf.x2 - leaf.x1;
+        let height = leaf.y2 - leaf.y1;
+        let can_split_x = width >= MIN_LEAF_SIZE * 2;
+        let can_split_y = height >= MIN_LEAF_SIZE * 2;
+
+        if !can_split_x && !can_split_y {
+            let center = self.carve_room(rng, map, leaf);
+            history.take_snapshot(map);
+            return center;
+        }
+
+        // prefer cutting across the longest side so leaves don't end
+        // up as long thin strips, otherwise flip a coin.
+        let split_x = if can_split_x && can_split_y {
+            if width > height * 2 {
+                true
+            } else if height > width * 2 {
+                false
+            } else {
+                rng.range(0, 2) == 0
+            }
+        } else {
+            can_split_x
+        };
+
+        let (first, second) = if split_x {
+            let split = rng.range(MIN_LEAF_SIZE, width - MIN_LEAF_SIZE + 1);
+            (Rect::new(leaf.x1, leaf.y1, split, height),
+             Rect::new(leaf.x1 + split, leaf.y1, width - split, height))
+        } else {
+            let split = rng.range(MIN_LEAF_SIZE, height - MIN_LEAF_SIZE + 1);
+            (Rect::new(leaf.x1, leaf.y1, width, split),
+             Rect::new(leaf.x1, leaf.y1 + split, width, height - split))
+        };
+
+        let (first_x, first_y) = self.partition(rng, map, history, &first);
+        let (second_x, second_y) = self.partition(rng, map, history, &second);
+
+        // join the siblings together
+        if rng.range(0, 2) == 1 {
+            apply_horizontal_tunnel(map, first_x, second_x, first_y);
+            apply_vertical_tunnel(map, first_y, second_y, second_x);
+        } else {
+            apply_vertical_tunnel(map, first_y, second_y, first_x);
+            apply_horizontal_tunnel(map, first_x, second_x, second_y);
+        }
+
+        history.take_snapshot(map);
+
+        if rng.range(0, 2) == 1 {
+            (first_x, first_y)
+        } else {
+            (second_x, second_y)
+        }
+    }
+
+    fn carve_room(&mut self, rng: &mut RandomNumberGenerator, map: &mut Map, leaf: &Rect) -> (i32, i32) {
+        let width = leaf.x2 - leaf.x1;
+        let height = leaf.y2 - leaf.y1;
+
+        // the room is kept at least one tile short of the leaf's far
+        // edges, so neighbouring leaves always have a wall between them.
+        let w = rng.range(MIN_ROOM_SIZE, width - 1);
+        let h = rng.range(MIN_ROOM_SIZE, height - 1);
+        let x = leaf.x1 + rng.range(0, width - w);
+        let y = leaf.y1 + rng.range(0, height - h);
+
+        let room = Rect::new(x, y, w, h);
+        apply_room_to_map(map, &room);
+        map.rooms.push(room);
+
+        room.center()
+    }
+}
@@ -0,0 +1,142 @@
+use super::{ InitialMapBuilder, MetaMapBuilder, BuilderMap, Map, TileType, Position,
+             remove_unreachable_areas_returning_most_distant,
+             find_central_floor };
+
+use rltk::RandomNumberGenerator;
+
+/// Tuning knobs for the cave smoothing.
+///
+/// Every pass counts the walls in the eight tiles around each cell:
+/// a wall with fewer than `death_limit` wall neighbours crumbles into
+/// floor, and a floor with more than `birth_limit` becomes a wall.
+#[derive(Clone, Copy)]
+pub struct CellularAutomataSettings {
+    pub wall_percent: i32,
+    pub iterations: i32,
+    pub birth_limit: i32,
+    pub death_limit: i32,
+}
+
+impl Default for CellularAutomataSettings {
+    fn default() -> Self {
+        CellularAutomataSettings {
+            wall_percent: 45,
+            iterations: 15,
+            birth_limit: 4,
+            death_limit: 4,
+        }
+    }
+}
+
+/// Generates caves from random noise. It can also be added later in
+/// a chain, where it only runs the smoothing passes, wearing down
+/// whatever the earlier builders left behind.
+pub struct CellularAutomataBuilder {
+    settings: CellularAutomataSettings,
+}
+
+impl InitialMapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl MetaMapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        for _ in 0..self.settings.iterations {
+            self.smooth(&mut build_data.map);
+            build_data.history.take_snapshot(&build_data.map);
+        }
+    }
+}
+
+impl CellularAutomataBuilder {
+    pub fn new() -> Box<CellularAutomataBuilder> {
+        CellularAutomataBuilder::with_settings(CellularAutomataSettings::default())
+    }
+
+    pub fn with_settings(settings: CellularAutomataSettings) -> Box<CellularAutomataBuilder> {
+        Box::new(CellularAutomataBuilder { settings })
+    }
+
+    /// A few passes of the default rules, for use as a meta builder.
+    pub fn smoothing(iterations: i32) -> Box<CellularAutomataBuilder> {
+        CellularAutomataBuilder::with_settings(CellularAutomataSettings {
+            iterations,
+            ..CellularAutomataSettings::default()
+        })
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let history = &mut build_data.history;
+
+        // seed the map with noise, leaving the outer edge as walls
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let roll = rng.roll_dice(1, 100);
+                let idx = map.xy_idx(x, y);
+
+                map.tiles[idx] = if roll > self.settings.wall_percent {
+                    TileType::Floor
+                } else {
+                    TileType::Wall
+                };
+            }
+        }
+
+        history.take_snapshot(map);
+
+        for _ in 0..self.settings.iterations {
+            self.smooth(map);
+            history.take_snapshot(map);
+        }
+
+        let start_idx = match find_central_floor(map) {
+            Some(idx) => idx,
+            None => {
+                // the noise smoothed into solid rock; dig out a
+                // single tile so the player has somewhere to stand.
+                let idx = map.xy_idx(map.width / 2, map.height / 2);
+                map.tiles[idx] = TileType::Floor;
+                idx
+            }
+        };
+
+        build_data.starting_position = Some(Position{
+            x: start_idx as i32 % map.width,
+            y: start_idx as i32 / map.width,
+        });
+
+        remove_unreachable_areas_returning_most_distant(map, start_idx);
+    }
+
+    /// Runs one pass of the automaton over the whole map.
+    fn smooth(&self, map: &mut Map) {
+        let mut new_tiles = map.tiles.clone();
+
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let idx = map.xy_idx(x, y);
+                let mut neighbours = 0;
+
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx != 0 || dy != 0) &&
+                           map.tiles[map.xy_idx(x + dx, y + dy)] == TileType::Wall {
+                            neighbours += 1;
+                        }
+                    }
+                }
+
+                new_tiles[idx] = match map.tiles[idx] {
+                    TileType::Wall if neighbours < self.settings.death_limit => TileType::Floor,
+                    TileType::Floor if neighbours > self.settings.birth_limit => TileType::Wall,
+                    tile => tile,
+                };
+            }
+        }
+
+        map.tiles = new_tiles;
+    }
+}
@@ -0,0 +1,237 @@
+use super::{ BuilderMap, Map, Rect, TileType, tile_walkable };
+
+use std::cmp::{ max, min };
+
+pub fn apply_room_to_map(map: &mut Map, room: &Rect) {
+    // inclusive range
+    // we want to visit every segment in the rooms rectangle
+    // and tile it with the Floor tile
+    //
+    // this needs to be done for both y and x.
+    for y in room.y1 + 1 ..= room.y2 {
+        for x in room.x1 + 1 ..= room.x2 {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_horizontal_tunnel(map: &mut Map, x1: i32, x2: i32, y: i32) {
+    for x in min(x1, x2) ..= max(x1, x2) {
+        let idx = map.xy_idx(x, y);
+
+        if idx > 0 && idx < map.width as usize * map.height as usize {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) {
+    for y in min(y1, y2) ..= max(y1, y2) {
+        let idx = map.xy_idx(x, y);
+
+        if idx > 0 && idx < map.width as usize * map.height as usize {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+/// What walking out from the player's start found.
+pub struct Reachability {
+    /// walkable tiles the player can get to, their own included
+    pub reachable: usize,
+    /// walkable tiles cut off from the start
+    pub unreachable: Vec<usize>,
+    /// the reachable floor tile that takes longest to walk to
+    pub most_distant: usize,
+}
+
+/// Finds out which walkable tiles can be walked to from `start_idx`,
+/// without changing any of them.
+pub fn check_reachability(map: &mut Map, start_idx: usize) -> Reachability {
+    // the dijkstra map paths through get_available_exits, which
+    // reads the blocked list rather than the tiles themselves.
+    map.populate_blocked();
+
+    // no single step costs as much as three, so three times the tile
+    // count is a depth nothing reachable can fall outside of.
+    let map_starts: Vec<usize> = vec![start_idx];
+    let max_depth = map.tiles.len() as f32 * 3.0;
+    let dijkstra_map = rltk::DijkstraMap::new(map.width as usize, map.height as usize,
+                                              &map_starts, map, max_depth);
+    let mut report = Reachability{ reachable: 0, unreachable: Vec::new(), most_distant: start_idx };
+    let mut most_distant = 0.0f32;
+
+    for (i, tile) in map.tiles.iter().enumerate() {
+        if !tile_walkable(*tile) {
+            continue;
+        }
+
+        let distance_to_start = dijkstra_map.map[i];
+
+        if distance_to_start == f32::MAX {
+            report.unreachable.push(i);
+        } else {
+            report.reachable += 1;
+
+            if *tile == TileType::Floor && distance_to_start > most_distant {
+                report.most_distant = i;
+                most_distant = distance_to_start;
+            }
+        }
+    }
+
+    report
+}
+
+/// Walls off every walkable tile that cannot be walked to from
+/// `start_idx`, and returns the index of the reachable floor tile
+/// furthest from it.
+pub fn remove_unreachable_areas_returning_most_distant(map: &mut Map, start_idx: usize) -> usize {
+    let report = check_reachability(map, start_idx);
+
+    for idx in report.unreachable.iter() {
+        map.tiles[*idx] = TileType::Wall;
+    }
+
+    report.most_distant
+}
+
+pub fn count_floor(map: &Map) -> usize {
+    map.tiles.iter().filter(|tile| **tile == TileType::Floor).count()
+}
+
+/// Sends diggers out one at a time until `floor_percent` of the map is
+/// floor, giving up after `max_diggers` rather than walking forever.
+/// `dig` is handed the map and how many diggers have gone before.
+pub fn dig_until_floor<F>(map: &mut Map, floor_percent: f32, max_diggers: i32, mut dig: F)
+    where F: FnMut(&mut Map, i32)
+{
+    let desired_floor_tiles = (floor_percent * (map.width * map.height) as f32) as usize;
+    let mut digger_count = 0;
+
+    while count_floor(map) < desired_floor_tiles && digger_count < max_diggers {
+        dig(map, digger_count);
+        digger_count += 1;
+    }
+}
+
+/// Returns the floor tile nearest the center of the map, used by
+/// generators that have no rooms to place the player in.
+pub fn find_central_floor(map: &Map) -> Option<usize> {
+    let center = rltk::Point::new(map.width / 2, map.height / 2);
+
+    map.tiles.iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile == TileType::Floor)
+        .map(|(idx, _)| {
+            let pt = rltk::Point::new(idx as i32 % map.width, idx as i32 / map.width);
+            (idx, rltk::DistanceAlg::PythagorasSquared.distance2d(center, pt))
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .map(|(idx, _)| idx)
+}
+
+/// What a character in a prefab template or level file stands for.
+pub enum Glyph {
+    Tile(TileType),
+    /// the player's starting tile
+    Start,
+    /// something to spawn, by name
+    Spawn(&'static str),
+}
+
+/// Looks up a template character. The start and spawns stand on floor.
+/// These are the characters `tile_char` writes a map out with.
+pub fn read_glyph(ch: char) -> Option<Glyph> {
+    let glyph = match ch {
+        ' ' | '.' => Glyph::Tile(TileType::Floor),
+        '#' => Glyph::Tile(TileType::Wall),
+        '>' => Glyph::Tile(TileType::DownStairs),
+        '<' => Glyph::Tile(TileType::UpStairs),
+        '~' => Glyph::Tile(TileType::ShallowWater),
+        'W' => Glyph::Tile(TileType::DeepWater),
+        'L' => Glyph::Tile(TileType::Lava),
+        '"' => Glyph::Tile(TileType::Grass),
+        '=' => Glyph::Tile(TileType::Bridge),
+        ';' => Glyph::Tile(TileType::Rubble),
+        'G' => Glyph::Tile(TileType::GlassWall),
+        '@' => Glyph::Start,
+        'g' => Glyph::Spawn("Goblin"),
+        'o' => Glyph::Spawn("Ork"),
+        '+' => Glyph::Spawn("Door"),
+        '!' => Glyph::Spawn("Health Potion"),
+        _ => return None,
+    };
+
+    Some(glyph)
+}
+
+/// The template character for a spawn, if templates have one for it.
+pub fn spawn_glyph(name: &str) -> Option<char> {
+    match name {
+        "Goblin" => Some('g'),
+        "Ork" => Some('o'),
+        "Door" => Some('+'),
+        "Health Potion" => Some('!'),
+        _ => None,
+    }
+}
+
+/// Mirroring applied by `paint`, around the center of the map.
+#[derive(PartialEq, Copy, Clone)]
+pub enum Symmetry {
+    None,
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+/// Digs out floor at `x`, `y` along with any mirror images the
+/// symmetry mode asks for.
+pub fn paint(map: &mut Map, mode: Symmetry, brush_size: i32, x: i32, y: i32) {
+    let center_x = map.width / 2;
+    let center_y = map.height / 2;
+    let mirror_x = center_x - (x - center_x);
+    let mirror_y = center_y - (y - center_y);
+
+    apply_paint(map, brush_size, x, y);
+
+    if mode == Symmetry::Horizontal || mode == Symmetry::Both {
+        apply_paint(map, brush_size, mirror_x, y);
+    }
+
+    if mode == Symmetry::Vertical || mode == Symmetry::Both {
+        apply_paint(map, brush_size, x, mirror_y);
+    }
+
+    if mode == Symmetry::Both {
+        apply_paint(map, brush_size, mirror_x, mirror_y);
+    }
+}
+
+/// Digs out a square of floor `brush_size` tiles across, centered on
+/// `x`, `y`, without ever breaking through the map's outer wall.
+fn apply_paint(map: &mut Map, brush_size: i32, x: i32, y: i32) {
+    let half_brush = brush_size / 2;
+
+    for brush_y in y - half_brush ..= y - half_brush + brush_size - 1 {
+        for brush_x in x - half_brush ..= x - half_brush + brush_size - 1 {
+            if brush_x > 0 && brush_x < map.width - 1 &&
+               brush_y > 0 && brush_y < map.height - 1 {
+                let idx = map.xy_idx(brush_x, brush_y);
+                map.tiles[idx] = TileType::Floor;
+            }
+        }
+    }
+}
+
+/// True if `room` is the one the player starts in. Until something in
+/// the chain has picked a start, the first room is taken to be theirs.
+pub fn is_starting_room(build_data: &BuilderMap, room_idx: usize, room: &Rect) -> bool {
+    match build_data.starting_position {
+        Some(start) => start.x > room.x1 && start.x <= room.x2 &&
+                       start.y > room.y1 && start.y <= room.y2,
+        None => room_idx == 0,
+    }
+}
@@ -0,0 +1,74 @@
+use super::{ MetaMapBuilder, BuilderMap, TileType, Position };
+
+use rltk::{ RandomNumberGenerator, DistanceAlg, Point };
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum XStart {
+    Left,
+    Right,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum YStart {
+    Top,
+    Bottom,
+}
+
+/// Starts the player as close as possible to one corner of the map:
+/// in the middle of the nearest room if the map has rooms, otherwise
+/// on the nearest floor tile.
+pub struct CornerStartingPosition {
+    x: XStart,
+    y: YStart,
+}
+
+impl MetaMapBuilder for CornerStartingPosition {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl CornerStartingPosition {
+    pub fn new(x: XStart, y: YStart) -> Box<CornerStartingPosition> {
+        Box::new(CornerStartingPosition { x, y })
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+
+        // the corner tiles themselves are always part of the outer wall
+        let corner = Point::new(
+            match self.x {
+                XStart::Left => 1,
+                XStart::Right => map.width - 2,
+            },
+            match self.y {
+                YStart::Top => 1,
+                YStart::Bottom => map.height - 2,
+            }
+        );
+
+        let distance = |pt: &Point| DistanceAlg::PythagorasSquared.distance2d(corner, *pt);
+
+        let candidates: Vec<Point> = if map.rooms.is_empty() {
+            map.tiles.iter()
+                .enumerate()
+                .filter(|(_, tile)| **tile == TileType::Floor)
+                .map(|(idx, _)| Point::new(idx as i32 % map.width, idx as i32 / map.width))
+                .collect()
+        } else {
+            map.rooms.iter()
+                .map(|room| {
+                    let (x, y) = room.center();
+                    Point::new(x, y)
+                })
+                .collect()
+        };
+
+        let start = candidates.iter()
+            .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap())
+            .expect("There is no floor to start on");
+
+        build_data.starting_position = Some(Position{ x: start.x, y: start.y });
+    }
+}
@@ -0,0 +1,48 @@
+use super::{ MetaMapBuilder, BuilderMap, TileType, Position,
+             remove_unreachable_areas_returning_most_distant,
+             find_central_floor };
+
+use rltk::RandomNumberGenerator;
+
+/// Walls off anything the player can't walk to from their start, and
+/// drops whatever was due to spawn in there or on the player's tile.
+///
+/// Earlier steps can leave the start on a wall (a stamped section or
+/// a round of smoothing, say), in which case the player is moved to
+/// the floor nearest the center first.
+pub struct CullUnreachable {}
+
+impl MetaMapBuilder for CullUnreachable {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl CullUnreachable {
+    pub fn new() -> Box<CullUnreachable> {
+        Box::new(CullUnreachable {})
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+
+        let start_idx = match build_data.starting_position {
+            Some(start) if map.tiles[map.xy_idx(start.x, start.y)] == TileType::Floor => {
+                map.xy_idx(start.x, start.y)
+            }
+            _ => {
+                let idx = find_central_floor(map).expect("There is no floor left to start on");
+                build_data.starting_position = Some(Position{
+                    x: idx as i32 % map.width,
+                    y: idx as i32 / map.width,
+                });
+                idx
+            }
+        };
+
+        remove_unreachable_areas_returning_most_distant(map, start_idx);
+
+        let tiles = &map.tiles;
+        build_data.spawn_list.retain(|(idx, _)| tiles[*idx] == TileType::Floor && *idx != start_idx);
+    }
+}
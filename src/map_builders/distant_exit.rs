@@ -0,0 +1,29 @@
+use super::{ MetaMapBuilder, BuilderMap, TileType,
+             remove_unreachable_areas_returning_most_distant };
+
+use rltk::RandomNumberGenerator;
+
+/// Puts the way down on the floor tile that takes longest to walk to
+/// from the start. Add it after the starting position has been chosen.
+pub struct DistantExit {}
+
+impl MetaMapBuilder for DistantExit {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl DistantExit {
+    pub fn new() -> Box<DistantExit> {
+        Box::new(DistantExit {})
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        let start = build_data.starting_position
+            .expect("Distant exit needs a starting position");
+        let start_idx = build_data.map.xy_idx(start.x, start.y);
+
+        let exit_idx = remove_unreachable_areas_returning_most_distant(&mut build_data.map, start_idx);
+        build_data.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+}
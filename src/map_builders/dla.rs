@@ -0,0 +1,201 @@
+use super::{ InitialMapBuilder, BuilderMap, Map, TileType, Position, paint, Symmetry,
+             dig_until_floor, remove_unreachable_areas_returning_most_distant };
+
+use rltk::RandomNumberGenerator;
+
+const MAX_DIGGERS: i32 = 5000; // particles released before settling for less floor
+const DIGGERS_PER_SNAPSHOT: i32 = 25; // most diggers only add a tile or two
+
+/// How each new particle moves before it sticks to the structure.
+#[derive(PartialEq, Copy, Clone)]
+pub enum DlaAlgorithm {
+    /// starts somewhere random and wanders until it touches floor
+    WalkInwards,
+    /// starts at the center and wanders until it touches wall
+    WalkOutwards,
+    /// starts somewhere random and heads straight for the center
+    CentralAttractor,
+}
+
+#[derive(Copy, Clone)]
+pub struct DlaSettings {
+    pub algorithm: DlaAlgorithm,
+    pub symmetry: Symmetry,
+    pub brush_size: i32,
+    pub floor_percent: f32,
+}
+
+impl DlaSettings {
+    pub fn walk_inwards() -> DlaSettings {
+        DlaSettings {
+            algorithm: DlaAlgorithm::WalkInwards,
+            symmetry: Symmetry::None,
+            brush_size: 1,
+            floor_percent: 0.25,
+        }
+    }
+
+    pub fn walk_outwards() -> DlaSettings {
+        DlaSettings {
+            algorithm: DlaAlgorithm::WalkOutwards,
+            symmetry: Symmetry::None,
+            brush_size: 2,
+            floor_percent: 0.25,
+        }
+    }
+
+    pub fn central_attractor() -> DlaSettings {
+        DlaSettings {
+            algorithm: DlaAlgorithm::CentralAttractor,
+            symmetry: Symmetry::None,
+            brush_size: 2,
+            floor_percent: 0.25,
+        }
+    }
+
+    /// A central attractor mirrored left to right, which tends to
+    /// come out looking like a bug pinned to a board.
+    pub fn insectoid() -> DlaSettings {
+        DlaSettings {
+            algorithm: DlaAlgorithm::CentralAttractor,
+            symmetry: Symmetry::Horizontal,
+            brush_size: 2,
+            floor_percent: 0.25,
+        }
+    }
+}
+
+pub struct DlaBuilder {
+    settings: DlaSettings,
+}
+
+impl InitialMapBuilder for DlaBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl DlaBuilder {
+    pub fn new(settings: DlaSettings) -> Box<DlaBuilder> {
+        Box::new(DlaBuilder { settings })
+    }
+
+    pub fn walk_inwards() -> Box<DlaBuilder> {
+        DlaBuilder::new(DlaSettings::walk_inwards())
+    }
+
+    pub fn walk_outwards() -> Box<DlaBuilder> {
+        DlaBuilder::new(DlaSettings::walk_outwards())
+    }
+
+    pub fn central_attractor() -> Box<DlaBuilder> {
+        DlaBuilder::new(DlaSettings::central_attractor())
+    }
+
+    pub fn insectoid() -> Box<DlaBuilder> {
+        DlaBuilder::new(DlaSettings::insectoid())
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let history = &mut build_data.history;
+
+        // carve a small cross in the middle of the map for the
+        // particles to start sticking to.
+        let start = Position{ x: map.width / 2, y: map.height / 2 };
+        build_data.starting_position = Some(start);
+        let start_idx = map.xy_idx(start.x, start.y);
+
+        for (x, y) in [(start.x, start.y), (start.x - 1, start.y), (start.x + 1, start.y),
+                       (start.x, start.y - 1), (start.x, start.y + 1)] {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Floor;
+        }
+
+        dig_until_floor(map, self.settings.floor_percent, MAX_DIGGERS, |map, digger_count| {
+            match self.settings.algorithm {
+                DlaAlgorithm::WalkInwards => self.step_walk_inwards(rng, map),
+                DlaAlgorithm::WalkOutwards => self.step_walk_outwards(rng, map, start),
+                DlaAlgorithm::CentralAttractor => self.step_central_attractor(rng, map, start),
+            }
+
+            if (digger_count + 1) % DIGGERS_PER_SNAPSHOT == 0 {
+                history.take_snapshot(map);
+            }
+        });
+
+        // mirrored paint can land clear of the main structure, so
+        // anything the player cannot walk to is filled back in.
+        remove_unreachable_areas_returning_most_distant(map, start_idx);
+    }
+
+    fn step_walk_inwards(&mut self, rng: &mut RandomNumberGenerator, map: &mut Map) {
+        let (mut digger_x, mut digger_y) = random_point(rng, map);
+        let (mut prev_x, mut prev_y) = (digger_x, digger_y);
+        let mut digger_idx = map.xy_idx(digger_x, digger_y);
+
+        while map.tiles[digger_idx] == TileType::Wall {
+            prev_x = digger_x;
+            prev_y = digger_y;
+            stagger(rng, map, &mut digger_x, &mut digger_y);
+            digger_idx = map.xy_idx(digger_x, digger_y);
+        }
+
+        paint(map, self.settings.symmetry, self.settings.brush_size, prev_x, prev_y);
+    }
+
+    fn step_walk_outwards(&mut self, rng: &mut RandomNumberGenerator, map: &mut Map, start: Position) {
+        let mut digger_x = start.x;
+        let mut digger_y = start.y;
+        let mut digger_idx = map.xy_idx(digger_x, digger_y);
+
+        while map.tiles[digger_idx] == TileType::Floor {
+            stagger(rng, map, &mut digger_x, &mut digger_y);
+            digger_idx = map.xy_idx(digger_x, digger_y);
+        }
+
+        paint(map, self.settings.symmetry, self.settings.brush_size, digger_x, digger_y);
+    }
+
+    fn step_central_attractor(&mut self, rng: &mut RandomNumberGenerator, map: &mut Map, start: Position) {
+        let (digger_x, digger_y) = random_point(rng, map);
+        let (mut prev_x, mut prev_y) = (digger_x, digger_y);
+
+        let path = rltk::line2d(
+            rltk::LineAlg::Bresenham,
+            rltk::Point::new(digger_x, digger_y),
+            rltk::Point::new(start.x, start.y)
+        );
+
+        // the line always ends on the starting tile, which is floor,
+        // so the particle is guaranteed to stick somewhere.
+        for step in path {
+            let idx = map.xy_idx(step.x, step.y);
+
+            if map.tiles[idx] == TileType::Floor {
+                break;
+            }
+
+            prev_x = step.x;
+            prev_y = step.y;
+        }
+
+        paint(map, self.settings.symmetry, self.settings.brush_size, prev_x, prev_y);
+    }
+}
+
+fn random_point(rng: &mut RandomNumberGenerator, map: &Map) -> (i32, i32) {
+    (rng.roll_dice(1, map.width - 3) + 1,
+     rng.roll_dice(1, map.height - 3) + 1)
+}
+
+/// Moves the digger one tile in a random direction, keeping it
+/// clear of the map's outer wall.
+fn stagger(rng: &mut RandomNumberGenerator, map: &Map, x: &mut i32, y: &mut i32) {
+    match rng.roll_dice(1, 4) {
+        1 => if *x > 2 { *x -= 1 },
+        2 => if *x < map.width - 2 { *x += 1 },
+        3 => if *y > 2 { *y -= 1 },
+        _ => if *y < map.height - 2 { *y += 1 },
+    }
+}
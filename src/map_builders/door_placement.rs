@@ -0,0 +1,84 @@
+use super::{ MetaMapBuilder, BuilderMap, Map, TileType };
+
+use rltk::RandomNumberGenerator;
+
+/// Hangs a door in each gap where a corridor meets a room. Only maps
+/// with rooms get doors; anything else is left as it was.
+pub struct DoorPlacement {}
+
+impl MetaMapBuilder for DoorPlacement {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl DoorPlacement {
+    pub fn new() -> Box<DoorPlacement> {
+        Box::new(DoorPlacement {})
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+        let mut doors: Vec<usize> = Vec::new();
+
+        for room in map.rooms.iter() {
+            // rooms are carved one tile inside their rectangle, so the
+            // rectangle's own edge is the wall around the floor.
+            let mut ring: Vec<(i32, i32)> = Vec::new();
+
+            for x in room.x1 ..= room.x2 + 1 {
+                ring.push((x, room.y1));
+                ring.push((x, room.y2 + 1));
+            }
+
+            for y in room.y1 + 1 ..= room.y2 {
+                ring.push((room.x1, y));
+                ring.push((room.x2 + 1, y));
+            }
+
+            for (x, y) in ring {
+                if !door_possible(map, x, y) {
+                    continue;
+                }
+
+                // a corridor running along the wall would otherwise
+                // get a whole row of doors side by side.
+                let next_to_door = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .iter()
+                    .any(|(nx, ny)| doors.contains(&map.xy_idx(*nx, *ny)));
+                let idx = map.xy_idx(x, y);
+
+                if !next_to_door && !doors.contains(&idx) {
+                    doors.push(idx);
+                }
+            }
+        }
+
+        for idx in doors {
+            if !build_data.spawn_list.iter().any(|(spawn_idx, _)| *spawn_idx == idx) {
+                build_data.spawn_list.push((idx, "Door".to_string()));
+            }
+        }
+    }
+}
+
+/// A door fits on a floor tile with wall on two opposite sides and
+/// open floor on the other two, like the mouth of a corridor.
+fn door_possible(map: &Map, x: i32, y: i32) -> bool {
+    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+        return false;
+    }
+
+    let tile = |x, y| map.tiles[map.xy_idx(x, y)];
+
+    if tile(x, y) != TileType::Floor {
+        return false;
+    }
+
+    let east_west = tile(x - 1, y) == TileType::Wall && tile(x + 1, y) == TileType::Wall &&
+                    tile(x, y - 1) == TileType::Floor && tile(x, y + 1) == TileType::Floor;
+    let north_south = tile(x, y - 1) == TileType::Wall && tile(x, y + 1) == TileType::Wall &&
+                      tile(x - 1, y) == TileType::Floor && tile(x + 1, y) == TileType::Floor;
+
+    east_west || north_south
+}
@@ -0,0 +1,120 @@
+use super::{ InitialMapBuilder, BuilderMap, TileType, Position, paint, Symmetry, dig_until_floor,
+             remove_unreachable_areas_returning_most_distant };
+
+use rltk::RandomNumberGenerator;
+
+const MAX_DIGGERS: i32 = 2000; // drunkards sent out before settling for less floor
+
+/// Where each new digger starts out.
+#[derive(PartialEq, Copy, Clone)]
+pub enum DrunkSpawnMode {
+    StartingPoint,
+    Random,
+}
+
+#[derive(Copy, Clone)]
+pub struct DrunkardSettings {
+    pub spawn_mode: DrunkSpawnMode,
+    pub drunken_lifetime: i32,
+    pub floor_percent: f32,
+    pub brush_size: i32,
+}
+
+impl DrunkardSettings {
+    /// A single, roughly round open space around the start.
+    pub fn open_area() -> DrunkardSettings {
+        DrunkardSettings {
+            spawn_mode: DrunkSpawnMode::StartingPoint,
+            drunken_lifetime: 400,
+            floor_percent: 0.5,
+            brush_size: 1,
+        }
+    }
+
+    /// Like the open area, but each digger sets off from somewhere
+    /// random, so the open space is spread across the whole map.
+    pub fn open_halls() -> DrunkardSettings {
+        DrunkardSettings {
+            spawn_mode: DrunkSpawnMode::Random,
+            drunken_lifetime: 400,
+            floor_percent: 0.5,
+            brush_size: 1,
+        }
+    }
+
+    /// Lots of short-lived diggers leave narrow, twisting tunnels.
+    pub fn winding_passages() -> DrunkardSettings {
+        DrunkardSettings {
+            spawn_mode: DrunkSpawnMode::Random,
+            drunken_lifetime: 100,
+            floor_percent: 0.4,
+            brush_size: 1,
+        }
+    }
+}
+
+pub struct DrunkardsWalkBuilder {
+    settings: DrunkardSettings,
+}
+
+impl InitialMapBuilder for DrunkardsWalkBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl DrunkardsWalkBuilder {
+    pub fn new(settings: DrunkardSettings) -> Box<DrunkardsWalkBuilder> {
+        Box::new(DrunkardsWalkBuilder { settings })
+    }
+
+    pub fn open_area() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings::open_area())
+    }
+
+    pub fn open_halls() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings::open_halls())
+    }
+
+    pub fn winding_passages() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings::winding_passages())
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let history = &mut build_data.history;
+
+        // the player always starts in the middle of the map
+        let start = Position{ x: map.width / 2, y: map.height / 2 };
+        build_data.starting_position = Some(start);
+        let start_idx = map.xy_idx(start.x, start.y);
+        map.tiles[start_idx] = TileType::Floor;
+
+        dig_until_floor(map, self.settings.floor_percent, MAX_DIGGERS, |map, digger_count| {
+            // the very first digger always leaves from the start,
+            // so the player is never left standing in a pocket.
+            let (mut drunk_x, mut drunk_y) =
+                if digger_count == 0 || self.settings.spawn_mode == DrunkSpawnMode::StartingPoint {
+                    (start.x, start.y)
+                } else {
+                    (rng.roll_dice(1, map.width - 3) + 1,
+                     rng.roll_dice(1, map.height - 3) + 1)
+                };
+
+            for _ in 0..self.settings.drunken_lifetime {
+                paint(map, Symmetry::None, self.settings.brush_size, drunk_x, drunk_y);
+
+                match rng.roll_dice(1, 4) {
+                    1 => if drunk_x > 2 { drunk_x -= 1 },
+                    2 => if drunk_x < map.width - 2 { drunk_x += 1 },
+                    3 => if drunk_y > 2 { drunk_y -= 1 },
+                    _ => if drunk_y < map.height - 2 { drunk_y += 1 },
+                }
+            }
+
+            history.take_snapshot(map);
+        });
+
+        remove_unreachable_areas_returning_most_distant(map, start_idx);
+    }
+}
@@ -0,0 +1,487 @@
+use super::{ InitialMapBuilder, BuilderMap, Rect, TileType, Position, read_glyph, spawn_glyph,
+             Glyph, tile_char };
+
+use rltk::RandomNumberGenerator;
+use rltk::rex::{ XpFile, XpLayer };
+
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+
+/// Why a level file couldn't be used.
+#[derive(Debug)]
+pub enum LevelFileError {
+    /// the file couldn't be read
+    Io(std::io::Error),
+    /// the file ends in `.xp`, but REX Paint didn't write it
+    NotRex(std::io::Error),
+    /// the level isn't the size of the map it is going into
+    WrongSize { expected: (i32, i32), found: (i32, i32) },
+    /// a row of a text level that isn't as long as the first
+    RaggedRow { line: usize, length: usize, expected: usize },
+    /// a character that isn't a template glyph, by line and column
+    UnknownGlyph { glyph: char, line: usize, column: usize },
+    /// a second '@', by line and column
+    SecondStart { line: usize, column: usize },
+    /// a line after a text level's map that isn't a room
+    BadAnnotation { line: usize, text: String },
+    /// a room that runs into the edge of the map, by its top left floor tile
+    RoomOnEdge { x: i32, y: i32 },
+    /// no '@', and no floor to start the player on instead
+    NoStart,
+}
+
+impl fmt::Display for LevelFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelFileError::Io(e) => write!(f, "{}", e),
+            LevelFileError::NotRex(e) => write!(f, "not a REX Paint file ({})", e),
+            LevelFileError::WrongSize{ expected, found } =>
+                write!(f, "the level is {}x{}, but the map is {}x{}",
+                       found.0, found.1, expected.0, expected.1),
+            LevelFileError::RaggedRow{ line, length, expected } =>
+                write!(f, "line {} is {} characters long, but the rows above it are {}",
+                       line, length, expected),
+            LevelFileError::UnknownGlyph{ glyph, line, column } =>
+                write!(f, "unknown glyph '{}' on line {}, column {}", glyph, line, column),
+            LevelFileError::SecondStart{ line, column } =>
+                write!(f, "a second '@' on line {}, column {}; a level has one starting tile",
+                       line, column),
+            LevelFileError::BadAnnotation{ line, text } =>
+                write!(f, "line {} should be a room, written 'room <x> <y> <width> <height>', not '{}'",
+                       line, text),
+            LevelFileError::RoomOnEdge{ x, y } =>
+                write!(f, "the room at {}, {} runs into the edge of the map", x, y),
+            LevelFileError::NoStart =>
+                write!(f, "the level has no '@' to start on, and no floor to start on instead"),
+        }
+    }
+}
+
+impl std::error::Error for LevelFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LevelFileError::Io(e) | LevelFileError::NotRex(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LevelFileError {
+    fn from(e: std::io::Error) -> LevelFileError {
+        LevelFileError::Io(e)
+    }
+}
+
+/// A hand-made level, read from a file before the chain runs, so that
+/// anything wrong with it is found without building anything.
+///
+/// Text levels are rows of the same characters prefab templates use,
+/// exactly as wide and high as the map. Rooms can be listed after a
+/// blank line, one `room <x> <y> <width> <height>` to a line, giving
+/// the top left tile and size of the room's floor.
+///
+/// REX Paint levels use the same characters on their first layer,
+/// where blank cells are solid rock. Anything painted on a second
+/// layer marks the floor of a room.
+#[derive(Clone)]
+pub struct LevelFile {
+    tiles: Vec<TileType>,
+    rooms: Vec<Rect>,
+    spawn_list: Vec<(usize, String)>,
+    starting_position: Option<Position>,
+}
+
+impl InitialMapBuilder for LevelFile {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        build_data.map.tiles = self.tiles.clone();
+        build_data.map.rooms = self.rooms.clone();
+        build_data.spawn_list.extend(self.spawn_list.iter().cloned());
+
+        // with no '@', the chain starts the player in the middle
+        build_data.starting_position = self.starting_position;
+    }
+}
+
+impl LevelFile {
+    /// Reads a REX Paint file if the path ends in `.xp`, and text if
+    /// it doesn't, for a map `width` by `height` tiles.
+    pub fn load(path: &Path, width: i32, height: i32) -> Result<LevelFile, LevelFileError> {
+        let is_rex = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("xp"));
+
+        if is_rex {
+            let xp = XpFile::read(&mut File::open(path)?).map_err(LevelFileError::NotRex)?;
+            LevelFile::from_rex(&xp, width, height)
+        } else {
+            LevelFile::from_text(&std::fs::read_to_string(path)?, width, height)
+        }
+    }
+
+    pub fn from_text(text: &str, width: i32, height: i32) -> Result<LevelFile, LevelFileError> {
+        let lines: Vec<&str> = text.lines().map(|line| line.trim_end_matches('\r')).collect();
+        let rows: Vec<&str> = lines.iter().copied().take_while(|line| !line.is_empty()).collect();
+
+        let row_width = rows.first().map_or(0, |row| row.chars().count());
+
+        for (i, row) in rows.iter().enumerate() {
+            let length = row.chars().count();
+
+            if length != row_width {
+                return Err(LevelFileError::RaggedRow{ line: i + 1, length, expected: row_width });
+            }
+        }
+
+        check_size(width, height, row_width as i32, rows.len() as i32)?;
+
+        let mut level = LevelFile::new(width, height);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, ch) in row.chars().enumerate() {
+                level.apply_glyph(width, x as i32, y as i32, ch)?;
+            }
+        }
+
+        // everything after the map should be a room
+        for (i, line) in lines.iter().enumerate().skip(rows.len()) {
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let bad_annotation = || LevelFileError::BadAnnotation{ line: i + 1, text: line.to_string() };
+            let mut words = line.split_whitespace();
+
+            if words.next() != Some("room") {
+                return Err(bad_annotation());
+            }
+
+            let numbers: Vec<i32> = words.map(|word| word.parse().map_err(|_| bad_annotation()))
+                .collect::<Result<_, _>>()?;
+
+            match numbers[..] {
+                [x, y, room_width, room_height] if room_width > 0 && room_height > 0 =>
+                    level.add_room(width, height, x, y, room_width, room_height)?,
+                _ => return Err(bad_annotation()),
+            }
+        }
+
+        level.check_start()?;
+        Ok(level)
+    }
+
+    pub fn from_rex(xp: &XpFile, width: i32, height: i32) -> Result<LevelFile, LevelFileError> {
+        let tiles = match xp.layers.first() {
+            Some(layer) => layer,
+            None => return Err(LevelFileError::WrongSize{ expected: (width, height), found: (0, 0) }),
+        };
+
+        check_size(width, height, tiles.width as i32, tiles.height as i32)?;
+
+        let mut level = LevelFile::new(width, height);
+
+        for y in 0..tiles.height {
+            for x in 0..tiles.width {
+                let ch = painted_glyph(tiles, x, y).unwrap_or('#');
+                level.apply_glyph(width, x as i32, y as i32, ch)?;
+            }
+        }
+
+        if let Some(rooms) = xp.layers.get(1) {
+            for (x, y, room_width, room_height) in painted_areas(rooms) {
+                level.add_room(width, height, x, y, room_width, room_height)?;
+            }
+        }
+
+        level.check_start()?;
+        Ok(level)
+    }
+
+    fn new(width: i32, height: i32) -> LevelFile {
+        LevelFile {
+            tiles: vec![TileType::Wall; (width * height) as usize],
+            rooms: Vec::new(),
+            spawn_list: Vec::new(),
+            starting_position: None,
+        }
+    }
+
+    /// Reads one character of the level onto its tile. Errors give the
+    /// line and column counting from 1, as an editor would show them.
+    fn apply_glyph(&mut self, width: i32, x: i32, y: i32, ch: char) -> Result<(), LevelFileError> {
+        let idx = (y * width + x) as usize;
+        let (line, column) = (y as usize + 1, x as usize + 1);
+        self.tiles[idx] = TileType::Floor;
+
+        match read_glyph(ch) {
+            Some(Glyph::Tile(tile)) => self.tiles[idx] = tile,
+            Some(Glyph::Spawn(name)) => self.spawn_list.push((idx, name.to_string())),
+            Some(Glyph::Start) => {
+                if self.starting_position.is_some() {
+                    return Err(LevelFileError::SecondStart{ line, column });
+                }
+
+                self.starting_position = Some(Position{ x, y });
+            }
+            None => return Err(LevelFileError::UnknownGlyph{ glyph: ch, line, column }),
+        }
+
+        Ok(())
+    }
+
+    /// Without an '@' the chain starts the player on the floor nearest
+    /// the middle, so there has to be some.
+    fn check_start(&self) -> Result<(), LevelFileError> {
+        if self.starting_position.is_none() && !self.tiles.contains(&TileType::Floor) {
+            return Err(LevelFileError::NoStart);
+        }
+
+        Ok(())
+    }
+
+    /// Adds a room by its floor, which has to stay inside the map's
+    /// outer wall.
+    fn add_room(&mut self, width: i32, height: i32,
+                x: i32, y: i32, room_width: i32, room_height: i32) -> Result<(), LevelFileError> {
+        if x < 1 || y < 1 || x + room_width > width - 1 || y + room_height > height - 1 {
+            return Err(LevelFileError::RoomOnEdge{ x, y });
+        }
+
+        // a room's rectangle sits one tile outside its floor
+        self.rooms.push(Rect::new(x - 1, y - 1, room_width, room_height));
+        Ok(())
+    }
+}
+
+/// A built level as rows of template characters, with the player's
+/// start and anything due to spawn drawn over the top: a text level
+/// that `LevelFile` can read back in.
+pub fn level_to_text(build_data: &BuilderMap) -> String {
+    let map = &build_data.map;
+    let mut chars: Vec<char> = map.tiles.iter().map(|tile| tile_char(*tile)).collect();
+
+    // a spawn's character stands for the floor under it as well, so
+    // one on anything else is left out to keep the tile, as is anything
+    // templates can't place
+    for (idx, name) in build_data.spawn_list.iter() {
+        if let Some(ch) = spawn_glyph(name).filter(|_| map.tiles[*idx] == TileType::Floor) {
+            chars[*idx] = ch;
+        }
+    }
+
+    if let Some(start) = build_data.starting_position {
+        let idx = map.xy_idx(start.x, start.y);
+
+        // below the first level the player starts on the way back up
+        if map.tiles[idx] != TileType::UpStairs {
+            chars[idx] = '@';
+        }
+    }
+
+    let mut text = String::with_capacity(chars.len() + map.height as usize);
+
+    for row in chars.chunks(map.width as usize) {
+        text.extend(row);
+        text.push('\n');
+    }
+
+    text
+}
+
+fn check_size(width: i32, height: i32, found_width: i32, found_height: i32) -> Result<(), LevelFileError> {
+    if found_width != width || found_height != height {
+        return Err(LevelFileError::WrongSize{
+            expected: (width, height),
+            found: (found_width, found_height),
+        });
+    }
+
+    Ok(())
+}
+
+/// The character painted on a REX Paint cell, or None if it is blank.
+fn painted_glyph(layer: &XpLayer, x: usize, y: usize) -> Option<char> {
+    let cell = layer.get(x, y)?;
+
+    match cell.ch {
+        0 | 32 => None,
+        ch if ch < 256 => Some(rltk::to_char(ch as u8)),
+        ch => Some(char::from_u32(ch).unwrap_or(char::REPLACEMENT_CHARACTER)),
+    }
+}
+
+/// The bounding boxes of each patch of painted cells on a layer, as
+/// `(x, y, width, height)`, in the order they're first met reading
+/// across the layer.
+fn painted_areas(layer: &XpLayer) -> Vec<(i32, i32, i32, i32)> {
+    let mut seen = vec![false; layer.width * layer.height];
+    let mut areas = Vec::new();
+
+    for y in 0..layer.height {
+        for x in 0..layer.width {
+            if seen[y * layer.width + x] || painted_glyph(layer, x, y).is_none() {
+                continue;
+            }
+
+            let (mut min_x, mut max_x, mut min_y, mut max_y) = (x, x, y, y);
+            let mut open = vec![(x, y)];
+            seen[y * layer.width + x] = true;
+
+            while let Some((cx, cy)) = open.pop() {
+                min_x = min_x.min(cx);
+                max_x = max_x.max(cx);
+                min_y = min_y.min(cy);
+                max_y = max_y.max(cy);
+
+                let neighbours = [(cx.wrapping_sub(1), cy), (cx + 1, cy),
+                                  (cx, cy.wrapping_sub(1)), (cx, cy + 1)];
+
+                for (nx, ny) in neighbours {
+                    if nx < layer.width && ny < layer.height && !seen[ny * layer.width + nx] &&
+                       painted_glyph(layer, nx, ny).is_some() {
+                        seen[ny * layer.width + nx] = true;
+                        open.push((nx, ny));
+                    }
+                }
+            }
+
+            areas.push((min_x as i32, min_y as i32,
+                        (max_x - min_x + 1) as i32, (max_y - min_y + 1) as i32));
+        }
+    }
+
+    areas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::random_builder;
+
+    const TEST_WIDTH: i32 = 8;
+    const TEST_HEIGHT: i32 = 5;
+
+    /// A small level of the test size, walled in, with the given middle
+    /// row and annotations.
+    fn level_text(middle: &str, annotations: &str) -> String {
+        format!("########\n#......#\n{}\n#......#\n########\n\n{}", middle, annotations)
+    }
+
+    fn read(text: &str) -> Result<LevelFile, LevelFileError> {
+        LevelFile::from_text(text, TEST_WIDTH, TEST_HEIGHT)
+    }
+
+    #[test]
+    fn reads_a_level() {
+        let level = read(&level_text("#.@g!+>#", "room 1 1 6 3")).expect("level should read");
+
+        assert_eq!(level.starting_position.map(|p| (p.x, p.y)), Some((2, 2)));
+        assert_eq!(level.tiles[2 * TEST_WIDTH as usize + 6], TileType::DownStairs);
+        assert_eq!(level.spawn_list.iter().map(|(_, name)| name.as_str()).collect::<Vec<_>>(),
+                   vec!["Goblin", "Health Potion", "Door"]);
+        assert_eq!(level.rooms.len(), 1);
+        assert_eq!((level.rooms[0].x1, level.rooms[0].y1), (0, 0));
+    }
+
+    #[test]
+    fn ragged_rows_are_rejected() {
+        match read(&level_text("#.@...#", "")) {
+            Err(LevelFileError::RaggedRow{ line: 3, length: 7, expected: 8 }) => {}
+            other => panic!("expected a ragged row, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn unknown_glyphs_are_rejected() {
+        match read(&level_text("#.@.X..#", "")) {
+            Err(LevelFileError::UnknownGlyph{ glyph: 'X', line: 3, column: 5 }) => {}
+            other => panic!("expected an unknown glyph, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn a_second_start_is_rejected() {
+        match read(&level_text("#.@..@.#", "")) {
+            Err(LevelFileError::SecondStart{ line: 3, column: 6 }) => {}
+            other => panic!("expected a second start, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn bad_annotations_are_rejected() {
+        for annotation in ["vault 1 1 2 2", "room 1 1 2", "room 1 1 2 2 2", "room a 1 2 2", "room 1 1 0 2"] {
+            match read(&level_text("#.@....#", annotation)) {
+                Err(LevelFileError::BadAnnotation{ line: 7, text }) => assert_eq!(text, annotation),
+                other => panic!("expected '{}' to be a bad annotation, got {:?}", annotation, other.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn rooms_on_the_edge_are_rejected() {
+        for (annotation, x, y) in [("room 0 1 2 2", 0, 1), ("room 1 0 2 2", 1, 0),
+                                   ("room 6 1 2 2", 6, 1), ("room 1 3 2 2", 1, 3)] {
+            match read(&level_text("#.@....#", annotation)) {
+                Err(LevelFileError::RoomOnEdge{ x: found_x, y: found_y }) =>
+                    assert_eq!((found_x, found_y), (x, y), "for '{}'", annotation),
+                other => panic!("expected '{}' to be on the edge, got {:?}", annotation, other.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn levels_of_the_wrong_size_are_rejected() {
+        let too_wide = "#########\n#.......#\n#.@.....#\n#.......#\n#########\n";
+        let too_short = "########\n#.@....#\n########\n";
+
+        for (text, found) in [(too_wide, (9, 5)), (too_short, (8, 3)), ("", (0, 0))] {
+            match read(text) {
+                Err(LevelFileError::WrongSize{ expected, found: size }) => {
+                    assert_eq!(expected, (TEST_WIDTH, TEST_HEIGHT));
+                    assert_eq!(size, found);
+                }
+                other => panic!("expected the wrong size, got {:?}", other.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn a_level_without_floor_has_nowhere_to_start() {
+        let solid = "########\n".repeat(TEST_HEIGHT as usize);
+        assert!(matches!(read(&solid), Err(LevelFileError::NoStart)));
+    }
+
+    /// What mapgen writes out reads back in as the same level, less
+    /// anything a template has no character for.
+    #[test]
+    fn generated_levels_read_back_in() {
+        const WIDTH: i32 = 80;
+        const HEIGHT: i32 = 50;
+
+        for seed in 0..10 {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let mut builder = random_builder(1, WIDTH, HEIGHT, &mut rng);
+            builder.build_map(&mut rng);
+            builder.validate();
+
+            let build_data = &builder.build_data;
+            let level = LevelFile::from_text(&level_to_text(build_data), WIDTH, HEIGHT)
+                .unwrap_or_else(|e| panic!("seed {} didn't read back in: {}", seed, e));
+
+            assert_eq!(level.tiles, build_data.map.tiles, "seed {} changed tiles", seed);
+
+            let start = build_data.starting_position.map(|p| (p.x, p.y));
+            assert_eq!(level.starting_position.map(|p| (p.x, p.y)), start, "seed {} moved the start", seed);
+
+            let mut written: Vec<(usize, String)> = build_data.spawn_list.iter()
+                .filter(|(idx, name)| build_data.map.tiles[*idx] == TileType::Floor
+                                      && spawn_glyph(name).is_some())
+                .cloned()
+                .collect();
+            let mut read_back = level.spawn_list.clone();
+            written.sort();
+            read_back.sort();
+            assert_eq!(read_back, written, "seed {} changed spawns", seed);
+        }
+    }
+}
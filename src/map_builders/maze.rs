@@ -0,0 +1,108 @@
+use super::{ InitialMapBuilder, BuilderMap, Map, TileType, Position };
+
+use rltk::RandomNumberGenerator;
+
+const CELLS_PER_SNAPSHOT: usize = 20; // a snapshot per cell would be thousands
+
+pub struct MazeBuilder {
+    loop_percent: i32,
+}
+
+impl InitialMapBuilder for MazeBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl MazeBuilder {
+    /// A perfect maze: exactly one path between any two points.
+    pub fn new() -> Box<MazeBuilder> {
+        MazeBuilder::with_loops(0)
+    }
+
+    /// A maze where `loop_percent` of the inner walls are knocked
+    /// down afterwards, giving more than one way around.
+    pub fn with_loops(loop_percent: i32) -> Box<MazeBuilder> {
+        Box::new(MazeBuilder { loop_percent })
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let history = &mut build_data.history;
+
+        // each maze cell is a single floor tile, with a tile of wall
+        // between it and its neighbours. Cell (cx, cy) sits on map
+        // tile (cx * 2 + 1, cy * 2 + 1).
+        let cells_wide = (map.width - 1) / 2;
+        let cells_high = (map.height - 1) / 2;
+        let mut visited = vec![false; (cells_wide * cells_high) as usize];
+        let mut stack: Vec<(i32, i32)> = vec![(0, 0)];
+        let mut carved = 1;
+
+        visited[0] = true;
+        carve_cell(map, 0, 0);
+
+        // recursive backtracker: keep walking to a random unvisited
+        // neighbour, and back up the stack when we hit a dead end.
+        while let Some(&(cx, cy)) = stack.last() {
+            let neighbours: Vec<(i32, i32)> = [(0, -1), (0, 1), (-1, 0), (1, 0)]
+                .iter()
+                .map(|(dx, dy)| (cx + dx, cy + dy))
+                .filter(|(nx, ny)| {
+                    *nx >= 0 && *nx < cells_wide && *ny >= 0 && *ny < cells_high &&
+                        !visited[(ny * cells_wide + nx) as usize]
+                })
+                .collect();
+
+            if neighbours.is_empty() {
+                stack.pop();
+                continue;
+            }
+
+            let (nx, ny) = neighbours[rng.random_slice_index(&neighbours).unwrap()];
+            visited[(ny * cells_wide + nx) as usize] = true;
+
+            // knock through the wall between the two cells
+            let wall_idx = map.xy_idx(cx + nx + 1, cy + ny + 1);
+            map.tiles[wall_idx] = TileType::Floor;
+            carve_cell(map, nx, ny);
+
+            carved += 1;
+            if carved % CELLS_PER_SNAPSHOT == 0 {
+                history.take_snapshot(map);
+            }
+
+            stack.push((nx, ny));
+        }
+
+        if self.loop_percent > 0 {
+            history.take_snapshot(map);
+            self.knock_out_walls(rng, map, cells_wide, cells_high);
+        }
+
+        // the first cell is always carved, so start the player there
+        build_data.starting_position = Some(Position{ x: 1, y: 1 });
+    }
+
+    /// Removes walls that sit directly between two cells; the pillars
+    /// at the corners are left alone so the maze keeps its shape.
+    fn knock_out_walls(&mut self, rng: &mut RandomNumberGenerator, map: &mut Map,
+                       cells_wide: i32, cells_high: i32) {
+        for y in 1..cells_high * 2 {
+            for x in 1..cells_wide * 2 {
+                let between_cells = (x % 2 == 0) != (y % 2 == 0);
+                let idx = map.xy_idx(x, y);
+
+                if between_cells && map.tiles[idx] == TileType::Wall &&
+                   rng.roll_dice(1, 100) <= self.loop_percent {
+                    map.tiles[idx] = TileType::Floor;
+                }
+            }
+        }
+    }
+}
+
+fn carve_cell(map: &mut Map, cx: i32, cy: i32) {
+    let idx = map.xy_idx(cx * 2 + 1, cy * 2 + 1);
+    map.tiles[idx] = TileType::Floor;
+}
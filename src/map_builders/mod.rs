@@ -0,0 +1,627 @@
+use super::{ Map, Rect, TileType, Position, spawner, tile_walkable, tile_char };
+
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+use std::path::Path;
+
+mod common;
+use common::*;
+mod simple_map;
+use simple_map::SimpleMapBuilder;
+mod bsp_dungeon;
+use bsp_dungeon::BspDungeonBuilder;
+mod cellular_automata;
+use cellular_automata::CellularAutomataBuilder;
+mod drunkard;
+use drunkard::DrunkardsWalkBuilder;
+mod maze;
+use maze::MazeBuilder;
+mod dla;
+use dla::DlaBuilder;
+mod voronoi;
+use voronoi::VoronoiCellBuilder;
+mod waveform_collapse;
+use waveform_collapse::WaveformCollapseBuilder;
+mod prefab_builder;
+use prefab_builder::{ PrefabBuilder, prefab_levels, prefab_sections };
+mod cull_unreachable;
+use cull_unreachable::CullUnreachable;
+mod door_placement;
+use door_placement::DoorPlacement;
+mod room_based_spawner;
+use room_based_spawner::RoomBasedSpawner;
+mod scattered_spawner;
+use scattered_spawner::ScatteredSpawner;
+mod region_spawner;
+use region_spawner::RegionSpawner;
+mod room_based_starting_position;
+use room_based_starting_position::RoomBasedStartingPosition;
+mod corner_starting_position;
+use corner_starting_position::{ CornerStartingPosition, XStart, YStart };
+mod room_based_stairs;
+use room_based_stairs::RoomBasedStairs;
+mod distant_exit;
+use distant_exit::DistantExit;
+mod level_file;
+use level_file::LevelFile;
+pub use level_file::{ LevelFileError, level_to_text };
+
+// a level the player can walk less than this much of (as a percentage
+// of the map) is thrown away and another built in its place
+const MIN_REACHABLE_PERCENT: usize = 3;
+
+/// Everything a builder chain works on, handed from each step of the
+/// chain to the next.
+pub struct BuilderMap {
+    pub spawn_list: Vec<(usize, String)>,
+    pub map: Map,
+    pub starting_position: Option<Position>,
+    // floor tiles grouped into areas that each get their own monsters,
+    // for builders that split the map up that way
+    pub spawn_regions: Option<Vec<Vec<usize>>>,
+    pub history: MapHistory,
+}
+
+/// Copies of the map's tiles taken as a chain builds it, for the
+/// generation visualiser to play back. Nothing is kept unless the
+/// chain was asked to record, since a chain can take hundreds.
+#[derive(Default)]
+pub struct MapHistory {
+    recording: bool,
+    pub snapshots: Vec<Vec<TileType>>,
+}
+
+impl MapHistory {
+    /// Keeps a copy of the tiles, if they have changed since the last.
+    pub fn take_snapshot(&mut self, map: &Map) {
+        if self.recording && self.snapshots.last() != Some(&map.tiles) {
+            self.snapshots.push(map.tiles.clone());
+        }
+    }
+}
+
+/// The first step of a chain, which lays down the map's basic shape.
+pub trait InitialMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap);
+}
+
+/// A later step of a chain, which reworks whatever came before it.
+pub trait MetaMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap);
+}
+
+/// A level, described as one initial builder followed by any number
+/// of meta builders, run in the order they were added. The game only
+/// needs to know how to run a chain and never how a map is made.
+pub struct BuilderChain {
+    starter: Option<Box<dyn InitialMapBuilder>>,
+    builders: Vec<Box<dyn MetaMapBuilder>>,
+    pub build_data: BuilderMap,
+}
+
+impl BuilderChain {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> BuilderChain {
+        BuilderChain {
+            starter: None,
+            builders: Vec::new(),
+            build_data: BuilderMap {
+                spawn_list: Vec::new(),
+                map: Map::new(new_depth, width, height),
+                starting_position: None,
+                spawn_regions: None,
+                history: MapHistory::default(),
+            },
+        }
+    }
+
+    /// Keeps a snapshot of the map after each step of the chain, and
+    /// the finer steps of whichever builders take their own.
+    pub fn record_history(&mut self) {
+        self.build_data.history.recording = true;
+    }
+
+    pub fn start_with(&mut self, starter: Box<dyn InitialMapBuilder>) {
+        match self.starter {
+            None => self.starter = Some(starter),
+            Some(_) => panic!("A builder chain can only have one starting builder"),
+        };
+    }
+
+    pub fn with(&mut self, metabuilder: Box<dyn MetaMapBuilder>) {
+        self.builders.push(metabuilder);
+    }
+
+    pub fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        match &mut self.starter {
+            None => panic!("A builder chain can't run without a starting builder"),
+            Some(starter) => starter.build_map(rng, &mut self.build_data),
+        }
+
+        self.build_data.history.take_snapshot(&self.build_data.map);
+
+        for metabuilder in self.builders.iter_mut() {
+            metabuilder.build_map(rng, &mut self.build_data);
+            self.build_data.history.take_snapshot(&self.build_data.map);
+        }
+
+        // nothing in the chain picked a start, so use the middle
+        if self.build_data.starting_position.is_none() {
+            let map = &self.build_data.map;
+            let idx = find_central_floor(map).expect("The builder chain left no floor");
+            self.build_data.starting_position = Some(Position{
+                x: idx as i32 % map.width,
+                y: idx as i32 / map.width,
+            });
+        }
+
+        // below the first level, the player arrives on a way back up
+        if self.build_data.map.depth > 1 {
+            let start = self.get_starting_position();
+            let idx = self.build_data.map.xy_idx(start.x, start.y);
+            self.build_data.map.tiles[idx] = TileType::UpStairs;
+            self.build_data.history.take_snapshot(&self.build_data.map);
+        }
+    }
+
+    /// Walls off anything the player can't walk to from the start, and
+    /// checks what is left is worth playing: enough room to move around
+    /// in, and a way down.
+    pub fn validate(&mut self) -> bool {
+        let start = self.get_starting_position();
+        let map = &mut self.build_data.map;
+        let start_idx = map.xy_idx(start.x, start.y);
+        let report = check_reachability(map, start_idx);
+
+        if !report.unreachable.is_empty() {
+            rltk::console::log(format!("Walled off {} tiles the player can't reach",
+                                       report.unreachable.len()));
+
+            for idx in report.unreachable.iter() {
+                map.tiles[*idx] = TileType::Wall;
+            }
+
+            self.build_data.history.take_snapshot(map);
+        }
+
+        // nothing can stand in a wall, or swim in lava
+        let tiles = &map.tiles;
+        self.build_data.spawn_list.retain(|(idx, _)| tile_walkable(tiles[*idx]));
+
+        let min_reachable = map.tiles.len() * MIN_REACHABLE_PERCENT / 100;
+        report.reachable >= min_reachable && map.tiles.contains(&TileType::DownStairs)
+    }
+
+    pub fn get_starting_position(&self) -> Position {
+        self.build_data.starting_position.expect("The map has not been built yet")
+    }
+
+    /// Creates everything on the spawn list. The map must already be
+    /// in the ECS, since spawn positions are stored as tile indices.
+    pub fn spawn_entities(&mut self, ecs: &mut World) {
+        let mut monster_count = 0;
+
+        for entity in self.build_data.spawn_list.iter() {
+            spawner::spawn_entity(ecs, entity, &mut monster_count);
+        }
+    }
+}
+
+/// Rooms joined by corridors, which can be decorated with vaults and
+/// doors and get one monster per room.
+fn random_room_builder(rng: &mut RandomNumberGenerator, builder: &mut BuilderChain) {
+    match rng.roll_dice(1, 2) {
+        1 => builder.start_with(SimpleMapBuilder::new()),
+        _ => builder.start_with(BspDungeonBuilder::new()),
+    }
+
+    // the start comes first, so the player's room is left alone
+    match rng.roll_dice(1, 5) {
+        1 => builder.with(CornerStartingPosition::new(XStart::Left, YStart::Top)),
+        2 => builder.with(CornerStartingPosition::new(XStart::Right, YStart::Top)),
+        3 => builder.with(CornerStartingPosition::new(XStart::Left, YStart::Bottom)),
+        4 => builder.with(CornerStartingPosition::new(XStart::Right, YStart::Bottom)),
+        _ => builder.with(RoomBasedStartingPosition::new()),
+    }
+
+    if rng.roll_dice(1, 3) == 1 {
+        builder.with(PrefabBuilder::vaults());
+    }
+
+    if rng.roll_dice(1, 2) == 1 {
+        builder.with(DoorPlacement::new());
+    }
+
+    // stairs first, so nothing is spawned standing on them
+    builder.with(RoomBasedStairs::new());
+    builder.with(RoomBasedSpawner::new());
+}
+
+/// Caves, mazes and other levels without rooms. These may have a
+/// prefab section stamped somewhere on them.
+fn random_shape_builder(rng: &mut RandomNumberGenerator, builder: &mut BuilderChain) {
+    // voronoi hives get a group of monsters in each of their cells
+    let mut region_spawns = false;
+
+    // hand-drawn pieces are only used on maps big enough to hold them
+    let (width, height) = (builder.build_data.map.width, builder.build_data.map.height);
+
+    match rng.roll_dice(1, 17) {
+        1 => builder.start_with(CellularAutomataBuilder::new()),
+        2 => builder.start_with(DrunkardsWalkBuilder::open_area()),
+        3 => builder.start_with(DrunkardsWalkBuilder::open_halls()),
+        4 => builder.start_with(DrunkardsWalkBuilder::winding_passages()),
+        5 => builder.start_with(MazeBuilder::new()),
+        6 => builder.start_with(MazeBuilder::with_loops(10)),
+        7 => builder.start_with(DlaBuilder::walk_inwards()),
+        8 => builder.start_with(DlaBuilder::walk_outwards()),
+        9 => builder.start_with(DlaBuilder::central_attractor()),
+        10 => builder.start_with(DlaBuilder::insectoid()),
+        11 => builder.start_with(WaveformCollapseBuilder::rooms()),
+        12 => builder.start_with(WaveformCollapseBuilder::caverns()),
+        13..=15 => {
+            builder.start_with(match rng.roll_dice(1, 3) {
+                1 => VoronoiCellBuilder::pythagoras(),
+                2 => VoronoiCellBuilder::manhattan(),
+                _ => VoronoiCellBuilder::chebyshev(),
+            });
+            region_spawns = true;
+        }
+        16 if prefab_levels::ORK_WARREN.fits(width, height) => {
+            // a hand-made level is used just as it was drawn,
+            // stairs and all
+            builder.start_with(PrefabBuilder::constant(prefab_levels::ORK_WARREN));
+            return;
+        }
+        _ => {
+            // drunkards' halls worn smooth, like water-cut caverns
+            builder.start_with(DrunkardsWalkBuilder::open_halls());
+            builder.with(CellularAutomataBuilder::smoothing(2));
+            builder.with(CullUnreachable::new());
+        }
+    }
+
+    // monsters keep away from wherever the player ends up, so the
+    // start can be moved out to a corner.
+    if rng.roll_dice(1, 4) == 1 {
+        builder.with(CornerStartingPosition::new(XStart::Left, YStart::Top));
+    }
+
+    let section = match rng.roll_dice(1, 8) {
+        1 => Some(prefab_sections::ORK_FORT),
+        2 => Some(prefab_sections::GOBLIN_SHRINE),
+        3 => Some(prefab_sections::ORK_GATEHOUSE),
+        _ => None,
+    };
+
+    if let Some(section) = section.filter(|section| section.fits(width, height)) {
+        builder.with(PrefabBuilder::sectional(section));
+    }
+
+    builder.with(CullUnreachable::new());
+    builder.with(DistantExit::new());
+
+    if region_spawns {
+        builder.with(RegionSpawner::new());
+    } else {
+        builder.with(ScatteredSpawner::new());
+    }
+}
+
+pub fn random_builder(new_depth: i32, width: i32, height: i32,
+                      rng: &mut RandomNumberGenerator) -> BuilderChain {
+    let mut builder = BuilderChain::new(new_depth, width, height);
+
+    match rng.roll_dice(1, 2) {
+        1 => random_room_builder(rng, &mut builder),
+        _ => random_shape_builder(rng, &mut builder),
+    }
+
+    builder
+}
+
+/// Every generator `named_builder` knows, "random" included.
+pub const BUILDER_NAMES: &[&str] = &[
+    "random", "simple", "bsp", "cellular",
+    "drunkard_open_area", "drunkard_open_halls", "drunkard_winding_passages",
+    "maze", "maze_with_loops",
+    "dla_walk_inwards", "dla_walk_outwards", "dla_central_attractor", "dla_insectoid",
+    "voronoi_pythagoras", "voronoi_manhattan", "voronoi_chebyshev",
+    "wfc_rooms", "wfc_caverns", "ork_warren",
+];
+
+/// A chain for a single generator, picked by name, with only what it
+/// takes to play it added on: a start and a way down. "random" is the
+/// same mix of chains the game uses. None if the name isn't known, or
+/// the generator can't make a level that size.
+pub fn named_builder(name: &str, new_depth: i32, width: i32, height: i32,
+                     rng: &mut RandomNumberGenerator) -> Option<BuilderChain> {
+    if name == "random" {
+        return Some(random_builder(new_depth, width, height, rng));
+    }
+
+    let mut builder = BuilderChain::new(new_depth, width, height);
+
+    // room builders still need a start, some stairs and their spawns
+    // picking, while everything else picks its own start
+    let room_starter: Option<Box<dyn InitialMapBuilder>> = match name {
+        "simple" => Some(SimpleMapBuilder::new()),
+        "bsp" => Some(BspDungeonBuilder::new()),
+        _ => None,
+    };
+
+    if let Some(starter) = room_starter {
+        builder.start_with(starter);
+        builder.with(RoomBasedStartingPosition::new());
+        builder.with(RoomBasedStairs::new());
+        builder.with(RoomBasedSpawner::new());
+        return Some(builder);
+    }
+
+    let starter: Box<dyn InitialMapBuilder> = match name {
+        "cellular" => CellularAutomataBuilder::new(),
+        "drunkard_open_area" => DrunkardsWalkBuilder::open_area(),
+        "drunkard_open_halls" => DrunkardsWalkBuilder::open_halls(),
+        "drunkard_winding_passages" => DrunkardsWalkBuilder::winding_passages(),
+        "maze" => MazeBuilder::new(),
+        "maze_with_loops" => MazeBuilder::with_loops(10),
+        "dla_walk_inwards" => DlaBuilder::walk_inwards(),
+        "dla_walk_outwards" => DlaBuilder::walk_outwards(),
+        "dla_central_attractor" => DlaBuilder::central_attractor(),
+        "dla_insectoid" => DlaBuilder::insectoid(),
+        "voronoi_pythagoras" => VoronoiCellBuilder::pythagoras(),
+        "voronoi_manhattan" => VoronoiCellBuilder::manhattan(),
+        "voronoi_chebyshev" => VoronoiCellBuilder::chebyshev(),
+        "wfc_rooms" => WaveformCollapseBuilder::rooms(),
+        "wfc_caverns" => WaveformCollapseBuilder::caverns(),
+        "ork_warren" => {
+            if !prefab_levels::ORK_WARREN.fits(width, height) {
+                return None;
+            }
+
+            // drawn with its own stairs
+            builder.start_with(PrefabBuilder::constant(prefab_levels::ORK_WARREN));
+            return Some(builder);
+        }
+        _ => return None,
+    };
+
+    builder.start_with(starter);
+    builder.with(DistantExit::new());
+
+    // voronoi maps spawn a group in each of their regions instead
+    if name.starts_with("voronoi") {
+        builder.with(RegionSpawner::new());
+    } else {
+        builder.with(ScatteredSpawner::new());
+    }
+
+    Some(builder)
+}
+
+/// Builds random levels until one passes `validate`, and returns its
+/// chain ready to spawn from. With `record_history`, the chain keeps
+/// snapshots of how its level was built.
+pub fn build_random_level(new_depth: i32, width: i32, height: i32, record_history: bool,
+                          rng: &mut RandomNumberGenerator) -> BuilderChain {
+    loop {
+        let mut builder = random_builder(new_depth, width, height, rng);
+
+        if record_history {
+            builder.record_history();
+        }
+
+        builder.build_map(rng);
+
+        if builder.validate() {
+            return builder;
+        }
+
+        rltk::console::log("Rejected a level with too little to explore, building another");
+    }
+}
+
+/// A chain for a hand-made level: a REX Paint file if the path ends
+/// in `.xp`, otherwise text. The file is read and checked here, so a
+/// chain is only returned for a level that fits the map.
+pub fn level_from_file(path: &Path, new_depth: i32, width: i32, height: i32)
+                       -> Result<BuilderChain, LevelFileError> {
+    let level = LevelFile::load(path, width, height)?;
+    let mut builder = BuilderChain::new(new_depth, width, height);
+    builder.start_with(Box::new(level));
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_WIDTH: i32 = 80;
+    const TEST_HEIGHT: i32 = 50;
+    const TEST_SEEDS: u64 = 3;
+
+    /// Builds the named generator over a few seeds, and checks that
+    /// every walkable tile it leaves can be walked to from the start,
+    /// the way down included.
+    fn assert_connected(name: &str) {
+        for seed in 0..TEST_SEEDS {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let mut builder = named_builder(name, 1, TEST_WIDTH, TEST_HEIGHT, &mut rng)
+                .expect("Unknown generator");
+            builder.build_map(&mut rng);
+
+            let start = builder.get_starting_position();
+            let map = &mut builder.build_data.map;
+            let start_idx = map.xy_idx(start.x, start.y);
+            assert!(tile_walkable(map.tiles[start_idx]), "{} seed {} starts in a wall", name, seed);
+
+            let report = check_reachability(map, start_idx);
+            assert!(report.unreachable.is_empty(),
+                    "{} seed {} left {} tiles unreachable", name, seed, report.unreachable.len());
+            assert!(map.tiles.contains(&TileType::DownStairs), "{} seed {} has no way down", name, seed);
+        }
+    }
+
+    #[test]
+    fn simple_map_is_connected() {
+        assert_connected("simple");
+    }
+
+    #[test]
+    fn bsp_dungeon_is_connected() {
+        assert_connected("bsp");
+    }
+
+    #[test]
+    fn cellular_automata_is_connected() {
+        assert_connected("cellular");
+    }
+
+    #[test]
+    fn drunkards_walk_is_connected() {
+        assert_connected("drunkard_open_area");
+        assert_connected("drunkard_open_halls");
+        assert_connected("drunkard_winding_passages");
+    }
+
+    #[test]
+    fn maze_is_connected() {
+        assert_connected("maze");
+        assert_connected("maze_with_loops");
+    }
+
+    #[test]
+    fn dla_is_connected() {
+        assert_connected("dla_walk_inwards");
+        assert_connected("dla_walk_outwards");
+        assert_connected("dla_central_attractor");
+        assert_connected("dla_insectoid");
+    }
+
+    #[test]
+    fn voronoi_is_connected() {
+        assert_connected("voronoi_pythagoras");
+        assert_connected("voronoi_manhattan");
+        assert_connected("voronoi_chebyshev");
+    }
+
+    #[test]
+    fn waveform_collapse_is_connected() {
+        assert_connected("wfc_rooms");
+        assert_connected("wfc_caverns");
+    }
+
+    #[test]
+    fn prefab_level_is_connected() {
+        assert_connected("ork_warren");
+    }
+
+    #[test]
+    fn every_name_has_a_builder() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+
+        for name in BUILDER_NAMES {
+            assert!(named_builder(name, 1, TEST_WIDTH, TEST_HEIGHT, &mut rng).is_some(), "{}", name);
+        }
+
+        assert!(named_builder("nonsense", 1, TEST_WIDTH, TEST_HEIGHT, &mut rng).is_none());
+    }
+
+    #[test]
+    fn random_levels_are_valid() {
+        for seed in 0..20 {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let mut builder = build_random_level(2, TEST_WIDTH, TEST_HEIGHT, false, &mut rng);
+
+            let start = builder.get_starting_position();
+            let spawn_list = builder.build_data.spawn_list.clone();
+            let map = &mut builder.build_data.map;
+            let start_idx = map.xy_idx(start.x, start.y);
+            assert!(tile_walkable(map.tiles[start_idx]), "seed {} starts in a wall", seed);
+
+            map.populate_blocked();
+            let dijkstra_map = rltk::DijkstraMap::new(map.width as usize, map.height as usize,
+                                                      &[start_idx], map, map.tiles.len() as f32 * 3.0);
+            let exit_idx = map.tiles.iter().position(|tile| *tile == TileType::DownStairs)
+                .unwrap_or_else(|| panic!("seed {} has no way down", seed));
+            assert!(dijkstra_map.map[exit_idx] < f32::MAX, "seed {} can't reach the way down", seed);
+
+            for (idx, name) in spawn_list.iter() {
+                assert!(tile_walkable(map.tiles[*idx]),
+                        "seed {} spawns a {} on {:?}", seed, name, map.tiles[*idx]);
+            }
+        }
+    }
+
+    #[test]
+    fn nothing_spawns_on_the_way_down() {
+        for name in ["simple", "bsp", "random"] {
+            for seed in 0..10 {
+                let mut rng = RandomNumberGenerator::seeded(seed);
+                let mut builder = named_builder(name, 1, TEST_WIDTH, TEST_HEIGHT, &mut rng)
+                    .expect("Unknown generator");
+                builder.build_map(&mut rng);
+
+                let map = &builder.build_data.map;
+                let exit_idx = map.tiles.iter().position(|tile| *tile == TileType::DownStairs)
+                    .unwrap_or_else(|| panic!("{} seed {} has no way down", name, seed));
+                assert!(builder.build_data.spawn_list.iter().all(|(idx, _)| *idx != exit_idx),
+                        "{} seed {} spawns on the way down", name, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn voronoi_spawns_outside_the_starting_cell() {
+        for seed in 0..TEST_SEEDS {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let mut builder = named_builder("voronoi_pythagoras", 1, TEST_WIDTH, TEST_HEIGHT, &mut rng)
+                .expect("Unknown generator");
+            builder.build_map(&mut rng);
+
+            let start = builder.get_starting_position();
+            let build_data = &builder.build_data;
+            let start_idx = build_data.map.xy_idx(start.x, start.y);
+            let regions = build_data.spawn_regions.as_ref().expect("Voronoi maps leave spawn regions");
+            let start_region = regions.iter().find(|region| region.contains(&start_idx));
+
+            assert!(!build_data.spawn_list.is_empty(), "seed {} spawned nothing", seed);
+
+            for (idx, name) in build_data.spawn_list.iter() {
+                assert!(regions.iter().any(|region| region.contains(idx)),
+                        "seed {} spawned a {} outside every region", seed, name);
+                assert!(!start_region.is_some_and(|region| region.contains(idx)),
+                        "seed {} spawned a {} in the player's cell", seed, name);
+            }
+        }
+    }
+
+    #[test]
+    fn validate_walls_off_unreachable_tiles() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let mut builder = named_builder("simple", 1, TEST_WIDTH, TEST_HEIGHT, &mut rng).unwrap();
+        builder.build_map(&mut rng);
+
+        // a pocket of floor in the corner, out of reach behind the outer wall
+        let pocket = builder.build_data.map.xy_idx(0, 0);
+        builder.build_data.map.tiles[pocket] = TileType::Floor;
+        builder.build_data.spawn_list.push((pocket, "Goblin".to_string()));
+
+        assert!(builder.validate());
+        assert_eq!(builder.build_data.map.tiles[pocket], TileType::Wall);
+        assert!(builder.build_data.spawn_list.iter().all(|(idx, _)| *idx != pocket));
+    }
+
+    #[test]
+    fn validate_rejects_a_level_that_is_too_small() {
+        let mut builder = BuilderChain::new(1, TEST_WIDTH, TEST_HEIGHT);
+        let map = &mut builder.build_data.map;
+        let start = map.xy_idx(10, 10);
+        let exit = map.xy_idx(11, 10);
+        map.tiles[start] = TileType::Floor;
+        map.tiles[exit] = TileType::DownStairs;
+        builder.build_data.starting_position = Some(Position{ x: 10, y: 10 });
+
+        assert!(!builder.validate());
+    }
+}
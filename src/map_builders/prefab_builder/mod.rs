@@ -0,0 +1,253 @@
+use super::{ InitialMapBuilder, MetaMapBuilder, BuilderMap, Rect, TileType, Position,
+             apply_horizontal_tunnel, apply_vertical_tunnel,
+             find_central_floor, is_starting_room, read_glyph, Glyph };
+
+use rltk::RandomNumberGenerator;
+
+pub mod prefab_levels;
+pub mod prefab_sections;
+pub mod prefab_rooms;
+use prefab_levels::PrefabLevel;
+use prefab_sections::{ PrefabSection, HorizontalPlacement, VerticalPlacement };
+
+const VAULT_CHANCE: i32 = 3; // a big enough room has a 1 in 3 chance of a vault
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum PrefabMode {
+    /// the whole level comes from a template
+    Constant{ level: PrefabLevel },
+    /// a template is stamped over part of another builder's level
+    Sectional{ section: PrefabSection },
+    /// rooms from another builder are swapped for vaults
+    RoomVaults,
+}
+
+/// Builds levels from hand-drawn ASCII templates. A constant level
+/// starts a chain, while sections and vaults are stamped over the
+/// output of an earlier builder.
+///
+/// Templates are drawn with these characters:
+///
+/// - `#` wall, and `.` or a space for floor
+/// - `>` the way down, and `<` the way back up
+/// - `~` shallow water, `W` deep water and `L` lava
+/// - `"` grass, `=` a bridge, `;` rubble and `G` a glass wall
+/// - `@` where the player starts
+/// - `g` a goblin, `o` an ork, `+` a door and `!` a health potion, each
+///   standing on floor
+pub struct PrefabBuilder {
+    mode: PrefabMode,
+}
+
+impl InitialMapBuilder for PrefabBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl MetaMapBuilder for PrefabBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl PrefabBuilder {
+    fn new(mode: PrefabMode) -> Box<PrefabBuilder> {
+        Box::new(PrefabBuilder { mode })
+    }
+
+    pub fn constant(level: PrefabLevel) -> Box<PrefabBuilder> {
+        PrefabBuilder::new(PrefabMode::Constant{ level })
+    }
+
+    pub fn sectional(section: PrefabSection) -> Box<PrefabBuilder> {
+        PrefabBuilder::new(PrefabMode::Sectional{ section })
+    }
+
+    pub fn vaults() -> Box<PrefabBuilder> {
+        PrefabBuilder::new(PrefabMode::RoomVaults)
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        match self.mode {
+            PrefabMode::Constant{ level } => self.load_level(build_data, level),
+            PrefabMode::Sectional{ section } => self.apply_section(build_data, section),
+            PrefabMode::RoomVaults => self.apply_room_vaults(rng, build_data),
+        }
+    }
+
+    fn load_level(&mut self, build_data: &mut BuilderMap, level: PrefabLevel) {
+        let (width, height) = template_size(level.template);
+        let offset_x = (build_data.map.width - width) / 2;
+        let offset_y = (build_data.map.height - height) / 2;
+        let mut start = None;
+
+        for (x, y, ch) in template_cells(level.template) {
+            let idx = build_data.map.xy_idx(x + offset_x, y + offset_y);
+
+            if char_to_map(build_data, ch, idx) {
+                start = Some(idx);
+            }
+        }
+
+        let start_idx = start.or_else(|| find_central_floor(&build_data.map))
+            .expect("Prefab level has no floor to start on");
+        build_data.starting_position = Some(Position{
+            x: start_idx as i32 % build_data.map.width,
+            y: start_idx as i32 / build_data.map.width,
+        });
+    }
+
+    /// Stamps a section over the map. Anything it cuts off, including
+    /// the player's start, is left for `CullUnreachable` to tidy up.
+    fn apply_section(&mut self, build_data: &mut BuilderMap, section: PrefabSection) {
+        let (width, height) = template_size(section.template);
+
+        // the section is kept inside the map's outer wall
+        let chunk_x = match section.placement.0 {
+            HorizontalPlacement::Left => 1,
+            HorizontalPlacement::Center => (build_data.map.width - width) / 2,
+            HorizontalPlacement::Right => build_data.map.width - 1 - width,
+        };
+
+        let chunk_y = match section.placement.1 {
+            VerticalPlacement::Top => 1,
+            VerticalPlacement::Center => (build_data.map.height - height) / 2,
+            VerticalPlacement::Bottom => build_data.map.height - 1 - height,
+        };
+
+        let area = Rect::new(chunk_x, chunk_y, width - 1, height - 1);
+        stamp(build_data, section.template, &area);
+
+        // the section may have landed in solid rock, so tunnel from its
+        // edge to the nearest floor that was already there.
+        connect_area(build_data, &area);
+    }
+
+    fn apply_room_vaults(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        // the player's room is left alone
+        for (i, room) in build_data.map.rooms.clone().iter().enumerate() {
+            if is_starting_room(build_data, i, room) {
+                continue;
+            }
+
+            let room_width = room.x2 - room.x1;
+            let room_height = room.y2 - room.y1;
+
+            let candidates: Vec<&prefab_rooms::PrefabRoom> = prefab_rooms::VAULTS.iter()
+                .filter(|vault| {
+                    let (width, height) = template_size(vault.template);
+                    width <= room_width && height <= room_height
+                })
+                .collect();
+
+            if candidates.is_empty() || rng.roll_dice(1, VAULT_CHANCE) != 1 {
+                continue;
+            }
+
+            let vault = candidates[rng.random_slice_index(&candidates).unwrap()];
+            let (width, height) = template_size(vault.template);
+
+            // center the vault in the floor space of the room
+            let x = room.x1 + 1 + (room_width - width) / 2;
+            let y = room.y1 + 1 + (room_height - height) / 2;
+
+            stamp(build_data, vault.template, &Rect::new(x, y, width - 1, height - 1));
+            build_data.history.take_snapshot(&build_data.map);
+        }
+    }
+}
+
+/// Copies a template onto the map with its top left corner at the
+/// corner of `area`, replacing anything spawned there before.
+fn stamp(build_data: &mut BuilderMap, template: &str, area: &Rect) {
+    let width = build_data.map.width;
+    build_data.spawn_list.retain(|(idx, _)| {
+        let (x, y) = (*idx as i32 % width, *idx as i32 / width);
+        x < area.x1 || x > area.x2 || y < area.y1 || y > area.y2
+    });
+
+    for (x, y, ch) in template_cells(template) {
+        let idx = build_data.map.xy_idx(area.x1 + x, area.y1 + y);
+        char_to_map(build_data, ch, idx);
+    }
+}
+
+/// Digs a corridor from the floor of `area` nearest the player to
+/// the nearest floor outside of it.
+fn connect_area(build_data: &mut BuilderMap, area: &Rect) {
+    let map = &mut build_data.map;
+
+    // with no start chosen yet, head for the middle of the map
+    let start = match build_data.starting_position {
+        Some(pos) => rltk::Point::new(pos.x, pos.y),
+        None => rltk::Point::new(map.width / 2, map.height / 2),
+    };
+
+    let inside = |pt: &rltk::Point| {
+        pt.x >= area.x1 && pt.x <= area.x2 && pt.y >= area.y1 && pt.y <= area.y2
+    };
+
+    let floor: Vec<rltk::Point> = map.tiles.iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile == TileType::Floor)
+        .map(|(idx, _)| rltk::Point::new(idx as i32 % map.width, idx as i32 / map.width))
+        .collect();
+
+    let distance = |a: rltk::Point, b: rltk::Point| {
+        rltk::DistanceAlg::PythagorasSquared.distance2d(a, b)
+    };
+
+    let from = floor.iter()
+        .filter(|pt| inside(pt))
+        .min_by(|a, b| distance(**a, start).partial_cmp(&distance(**b, start)).unwrap());
+
+    let from = match from {
+        Some(from) => *from,
+        None => return,
+    };
+
+    let to = floor.iter()
+        .filter(|pt| !inside(pt))
+        .min_by(|a, b| distance(**a, from).partial_cmp(&distance(**b, from)).unwrap());
+
+    if let Some(to) = to {
+        apply_horizontal_tunnel(map, from.x, to.x, from.y);
+        apply_vertical_tunnel(map, from.y, to.y, to.x);
+    }
+}
+
+/// Applies a single template character to the map. Returns true if
+/// the character marks the player's starting tile.
+fn char_to_map(build_data: &mut BuilderMap, ch: char, idx: usize) -> bool {
+    build_data.map.tiles[idx] = TileType::Floor;
+
+    match read_glyph(ch) {
+        Some(Glyph::Tile(tile)) => build_data.map.tiles[idx] = tile,
+        Some(Glyph::Start) => return true,
+        Some(Glyph::Spawn(name)) => build_data.spawn_list.push((idx, name.to_string())),
+        None => rltk::console::log(format!("Unknown glyph in prefab template: {}", ch)),
+    }
+
+    false
+}
+
+/// The rows of a template, ignoring the blank lines either side of it.
+fn template_rows(template: &str) -> impl Iterator<Item = &str> {
+    template.lines().filter(|line| !line.is_empty())
+}
+
+fn template_size(template: &str) -> (i32, i32) {
+    let width = template_rows(template).map(|row| row.chars().count()).max().unwrap_or(0);
+    (width as i32, template_rows(template).count() as i32)
+}
+
+/// Every character in a template, along with its offset from the
+/// template's top left corner.
+fn template_cells(template: &str) -> impl Iterator<Item = (i32, i32, char)> + '_ {
+    template_rows(template)
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.chars().enumerate().map(move |(x, ch)| (x as i32, y as i32, ch))
+        })
+}
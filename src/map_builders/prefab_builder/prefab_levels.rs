@@ -0,0 +1,40 @@
+/// A complete, hand-drawn level. It is placed in the middle of the
+/// map, with everything outside it left as solid rock.
+#[derive(PartialEq, Copy, Clone)]
+pub struct PrefabLevel {
+    pub template: &'static str,
+}
+
+impl PrefabLevel {
+    /// True if the whole level fits on a map of the given size.
+    pub fn fits(&self, width: i32, height: i32) -> bool {
+        let (level_width, level_height) = super::template_size(self.template);
+        level_width <= width && level_height <= height
+    }
+}
+
+pub const ORK_WARREN: PrefabLevel = PrefabLevel {
+    template: ORK_WARREN_MAP,
+};
+
+const ORK_WARREN_MAP: &str = "
+##################################################
+#.........#############..........#################
+#....@....#############..........######...!.######
+#.........................o......######.....######
+#.........#############..........######..g..######
+#.........#######.#####..........######.....######
+#####.###########.##############.#######.#########
+#####.###########.##############.#######.#########
+#####.###########.##############..........########
+#####.###.........########################.#######
+#####.###.........########################.#######
+#####.###....g....########.......#########.#######
+#####.............########.......#########.#######
+#########.........########...o...........g.......#
+#########.........########.......#########.......#
+#############.############.......#########.......#
+#############.#############.#.#.##########.......#
+#############...........................o.......>#
+##################################################
+";
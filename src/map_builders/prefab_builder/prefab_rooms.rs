@@ -0,0 +1,82 @@
+/// A small set piece that can replace the inside of a generated room.
+/// Vaults keep a ring of floor around their edge, so corridors that
+/// led into the room still lead somewhere.
+#[derive(PartialEq, Copy, Clone)]
+pub struct PrefabRoom {
+    pub template: &'static str,
+}
+
+pub const GOBLIN_CAMP: PrefabRoom = PrefabRoom {
+    template: "
+......
+.#..#.
+..gg..
+.#..#.
+......
+",
+};
+
+pub const ORK_PILLARS: PrefabRoom = PrefabRoom {
+    template: "
+.......
+.#.o.#.
+.......
+.#.o.#.
+.......
+",
+};
+
+pub const GUARD_POST: PrefabRoom = PrefabRoom {
+    template: "
+.........
+.##...##.
+.#..g..#.
+.##...##.
+.........
+",
+};
+
+pub const FLOODED_CROSSING: PrefabRoom = PrefabRoom {
+    template: "
+.........
+.~~~=~~~.
+.~WW=WW~.
+.~WW=WW~.
+.~~~=~~~.
+.........
+",
+};
+
+pub const CRYSTAL_CELL: PrefabRoom = PrefabRoom {
+    template: "
+.......
+.GGGGG.
+.G.!.G.
+.G.o.G.
+.GG.GG.
+.......
+",
+};
+
+pub const LAVA_PIT: PrefabRoom = PrefabRoom {
+    template: "
+........
+.;;LL;;.
+.;LLLL;.
+.;;LL;;.
+........
+",
+};
+
+pub const GROVE: PrefabRoom = PrefabRoom {
+    template: r#"
+.......
+.""""".
+.""g"".
+.""""".
+.......
+"#,
+};
+
+pub const VAULTS: &[PrefabRoom] = &[GOBLIN_CAMP, ORK_PILLARS, GUARD_POST,
+                                    FLOODED_CROSSING, CRYSTAL_CELL, LAVA_PIT, GROVE];
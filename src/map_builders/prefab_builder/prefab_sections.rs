@@ -0,0 +1,90 @@
+#[derive(PartialEq, Copy, Clone)]
+pub enum HorizontalPlacement {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum VerticalPlacement {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// A hand-drawn piece of a level, stamped over the top of whatever
+/// another builder generated.
+#[derive(PartialEq, Copy, Clone)]
+pub struct PrefabSection {
+    pub template: &'static str,
+    pub placement: (HorizontalPlacement, VerticalPlacement),
+}
+
+impl PrefabSection {
+    /// True if the section fits inside the outer wall of a map of the
+    /// given size.
+    pub fn fits(&self, width: i32, height: i32) -> bool {
+        let (section_width, section_height) = super::template_size(self.template);
+        section_width + 2 <= width && section_height + 2 <= height
+    }
+}
+
+pub const ORK_FORT: PrefabSection = PrefabSection {
+    template: ORK_FORT_MAP,
+    placement: (HorizontalPlacement::Right, VerticalPlacement::Center),
+};
+
+const ORK_FORT_MAP: &str = "
+.....................
+.###################.
+.#.................#.
+.#..o..#.....#..o..#.
+.#.....#.....#.....#.
+.#######.....#######.
+.......#..o..#.......
+.......#.....#.......
+.......###.###.......
+.....................
+.......###.###.......
+.......#.....#.......
+.......#..o..#.......
+.#######.....#######.
+.#.....#.....#.....#.
+.#..o..#.....#..o..#.
+.#.................#.
+.###################.
+.....................
+";
+
+pub const GOBLIN_SHRINE: PrefabSection = PrefabSection {
+    template: GOBLIN_SHRINE_MAP,
+    placement: (HorizontalPlacement::Center, VerticalPlacement::Top),
+};
+
+const GOBLIN_SHRINE_MAP: &str = "
+...............
+.#####...#####.
+.#...#...#...#.
+.#.g.......g.#.
+.#...#.!.#...#.
+.#####.g.#####.
+...............
+";
+
+pub const ORK_GATEHOUSE: PrefabSection = PrefabSection {
+    template: ORK_GATEHOUSE_MAP,
+    placement: (HorizontalPlacement::Left, VerticalPlacement::Bottom),
+};
+
+const ORK_GATEHOUSE_MAP: &str = "
+..............
+.############.
+.#....##....#.
+.#.o..##..o.#.
+.#!.........#.
+.####....####.
+....#....#....
+....#.o..#....
+....##..##....
+..............
+";
@@ -0,0 +1,45 @@
+use super::{ MetaMapBuilder, BuilderMap, TileType, spawner };
+
+use rltk::RandomNumberGenerator;
+
+/// Puts a group of monsters in each of the spawn regions an earlier
+/// builder left behind, except the one the player starts in. Add it
+/// after the starting position has been chosen.
+pub struct RegionSpawner {}
+
+impl MetaMapBuilder for RegionSpawner {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl RegionSpawner {
+    pub fn new() -> Box<RegionSpawner> {
+        Box::new(RegionSpawner {})
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+        let regions = build_data.spawn_regions.as_ref()
+            .expect("Region spawning needs a builder that leaves spawn regions");
+        let start_idx = build_data.starting_position.map(|start| map.xy_idx(start.x, start.y));
+
+        for region in regions.iter() {
+            if start_idx.is_some_and(|idx| region.contains(&idx)) {
+                continue;
+            }
+
+            // builders after the one that drew the regions may have
+            // walled some of them in, or spawned things of their own
+            let floor: Vec<usize> = region.iter()
+                .copied()
+                .filter(|idx| map.tiles[*idx] == TileType::Floor)
+                .filter(|idx| !build_data.spawn_list.iter().any(|(spawn_idx, _)| spawn_idx == idx))
+                .collect();
+
+            if !floor.is_empty() {
+                spawner::spawn_region(rng, &floor, &mut build_data.spawn_list);
+            }
+        }
+    }
+}
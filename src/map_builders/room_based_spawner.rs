@@ -0,0 +1,27 @@
+use super::{ MetaMapBuilder, BuilderMap, spawner, is_starting_room };
+
+use rltk::RandomNumberGenerator;
+
+/// Puts a monster in every room except the one the player starts in.
+/// Add it after the starting position and stairs have been chosen.
+pub struct RoomBasedSpawner {}
+
+impl MetaMapBuilder for RoomBasedSpawner {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl RoomBasedSpawner {
+    pub fn new() -> Box<RoomBasedSpawner> {
+        Box::new(RoomBasedSpawner {})
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        for (i, room) in build_data.map.rooms.iter().enumerate() {
+            if !is_starting_room(build_data, i, room) {
+                spawner::spawn_room(rng, &build_data.map, room, &mut build_data.spawn_list);
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use super::{ MetaMapBuilder, BuilderMap, TileType, is_starting_room };
+
+use rltk::RandomNumberGenerator;
+
+/// Puts the way down in the middle of the last room that isn't the
+/// player's own.
+pub struct RoomBasedStairs {}
+
+impl MetaMapBuilder for RoomBasedStairs {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl RoomBasedStairs {
+    pub fn new() -> Box<RoomBasedStairs> {
+        Box::new(RoomBasedStairs {})
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        let rooms = &build_data.map.rooms;
+
+        // a single room map has nowhere else to put them
+        let room = rooms.iter()
+            .enumerate()
+            .rev()
+            .find(|(i, room)| !is_starting_room(build_data, *i, room))
+            .map(|(_, room)| *room)
+            .or_else(|| rooms.last().copied())
+            .expect("Room based stairs need a map with rooms");
+
+        let (x, y) = room.center();
+        let idx = build_data.map.xy_idx(x, y);
+        build_data.map.tiles[idx] = TileType::DownStairs;
+    }
+}
@@ -0,0 +1,25 @@
+use super::{ MetaMapBuilder, BuilderMap, Position };
+
+use rltk::RandomNumberGenerator;
+
+/// Starts the player in the middle of the first room.
+pub struct RoomBasedStartingPosition {}
+
+impl MetaMapBuilder for RoomBasedStartingPosition {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(build_data);
+    }
+}
+
+impl RoomBasedStartingPosition {
+    pub fn new() -> Box<RoomBasedStartingPosition> {
+        Box::new(RoomBasedStartingPosition {})
+    }
+
+    fn build(&mut self, build_data: &mut BuilderMap) {
+        let room = build_data.map.rooms.first()
+            .expect("Room based starting position needs a map with rooms");
+        let (x, y) = room.center();
+        build_data.starting_position = Some(Position{ x, y });
+    }
+}
@@ -0,0 +1,32 @@
+use super::{ MetaMapBuilder, BuilderMap, Position, spawner, find_central_floor };
+
+use rltk::RandomNumberGenerator;
+
+/// Drops monsters on floor tiles all over the map, for levels that
+/// have no rooms to put them in. Monsters keep their distance from
+/// the start, so add it after the starting position has been chosen.
+pub struct ScatteredSpawner {}
+
+impl MetaMapBuilder for ScatteredSpawner {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl ScatteredSpawner {
+    pub fn new() -> Box<ScatteredSpawner> {
+        Box::new(ScatteredSpawner {})
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &build_data.map;
+
+        // keep clear of where the player will most likely end up
+        let start = build_data.starting_position.unwrap_or_else(|| {
+            let idx = find_central_floor(map).unwrap_or(0);
+            Position{ x: idx as i32 % map.width, y: idx as i32 / map.width }
+        });
+
+        spawner::spawn_scattered(rng, map, &start, &mut build_data.spawn_list);
+    }
+}
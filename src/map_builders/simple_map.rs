@@ -0,0 +1,86 @@
+use super::{ InitialMapBuilder, BuilderMap, Rect,
+             apply_room_to_map, apply_horizontal_tunnel,
+             apply_vertical_tunnel };
+
+use rltk::RandomNumberGenerator;
+
+pub struct SimpleMapBuilder {}
+
+impl InitialMapBuilder for SimpleMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.rooms_and_corridors(rng, build_data);
+    }
+}
+
+impl SimpleMapBuilder {
+    pub fn new() -> Box<SimpleMapBuilder> {
+        Box::new(SimpleMapBuilder {})
+    }
+
+    fn rooms_and_corridors(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        const MAX_ROOMS: i32 = 30; // maximum number of rooms possible
+        const MIN_SIZE: i32 = 6; // minimum room size in tiles
+        const MAX_SIZE: i32 = 10; // maximum room size in tiles
+
+        let map = &mut build_data.map;
+        let history = &mut build_data.history;
+
+        for _ in 0..MAX_ROOMS {
+            // generate a width/height for a room by obtaining
+            // a value between the rang of MIN_SIZE and MAX_SIZE
+            // using the Rltk random number generator
+            let w = rng.range(MIN_SIZE, MAX_SIZE);
+            let h = rng.range(MIN_SIZE, MAX_SIZE);
+
+            // generate the x and y size of of the rectangle
+            // in this case, we're obtaining a value between:
+            //
+            //  x = 1 -> the screen width minus the generated width of the
+            //           the rectangle.
+            //
+            //  y = 1 -> the screen height minus the generated height of
+            //           the rectangle
+            let x = rng.roll_dice(1, map.width - w - 1) - 1;
+            let y = rng.roll_dice(1, map.height - h - 1) - 1;
+            let new_room = Rect::new(x, y, w, h);
+            let mut ok = true;
+
+            // check to verify that the new room DOES NOT intersect
+            // in anyway the other rooms that have previously been generated
+            // due to the randomness, we are guaranteeded AT LEAST one room
+            // and AT MOST the maximum rooms
+            for other_room in map.rooms.iter() {
+                if new_room.intersect(other_room) {
+                    ok = false;
+                }
+            }
+
+            if ok {
+                // bind the new room to the map
+                apply_room_to_map(map, &new_room);
+
+                // if there is more than one room in the vector of Rectangles.
+                if !map.rooms.is_empty() {
+                    // obtain the center of the new room
+                    let (new_x, new_y) = new_room.center();
+
+                    // obtain the center of the last added room
+                    let room_len = map.rooms.len() - 1;
+                    let (prev_x, prev_y) = map.rooms[room_len].center();
+
+                    // randomly decide how the tunnels should be connected
+                    if rng.range(0, 2) == 1 {
+                        apply_horizontal_tunnel(map, prev_x, new_x, prev_y);
+                        apply_vertical_tunnel(map, prev_y, new_y, new_x);
+                    } else {
+                        apply_vertical_tunnel(map, prev_y, new_y, prev_x);
+                        apply_horizontal_tunnel(map, prev_x, new_x, new_y);
+                    }
+                }
+
+                map.rooms.push(new_room);
+                history.take_snapshot(map);
+            }
+        }
+    }
+}
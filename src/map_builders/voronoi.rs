@@ -0,0 +1,152 @@
+use super::{ InitialMapBuilder, BuilderMap, Map, TileType, Position,
+             remove_unreachable_areas_returning_most_distant,
+             find_central_floor };
+
+use rltk::{ RandomNumberGenerator, DistanceAlg, Point };
+
+use std::collections::BTreeMap;
+
+const SEED_COUNT: usize = 64; // number of cells in the hive
+
+/// Splits the map into a hive of cells around random seed points.
+/// Each cell's floor is left as a spawn region, for a `RegionSpawner`
+/// to give its own group of monsters.
+pub struct VoronoiCellBuilder {
+    distance_algorithm: DistanceAlg,
+}
+
+impl InitialMapBuilder for VoronoiCellBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl VoronoiCellBuilder {
+    pub fn new(distance_algorithm: DistanceAlg) -> Box<VoronoiCellBuilder> {
+        Box::new(VoronoiCellBuilder { distance_algorithm })
+    }
+
+    pub fn pythagoras() -> Box<VoronoiCellBuilder> {
+        VoronoiCellBuilder::new(DistanceAlg::Pythagoras)
+    }
+
+    pub fn manhattan() -> Box<VoronoiCellBuilder> {
+        VoronoiCellBuilder::new(DistanceAlg::Manhattan)
+    }
+
+    pub fn chebyshev() -> Box<VoronoiCellBuilder> {
+        VoronoiCellBuilder::new(DistanceAlg::Chebyshev)
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let map = &mut build_data.map;
+        let history = &mut build_data.history;
+
+        // scatter the seeds, making sure no two share a tile
+        let mut seeds: Vec<Point> = Vec::new();
+
+        while seeds.len() < SEED_COUNT {
+            let seed = Point::new(rng.roll_dice(1, map.width - 2),
+                                  rng.roll_dice(1, map.height - 2));
+
+            if !seeds.contains(&seed) {
+                seeds.push(seed);
+            }
+        }
+
+        // every tile belongs to whichever seed is closest to it
+        let mut membership = vec![0usize; map.tiles.len()];
+
+        for (idx, member) in membership.iter_mut().enumerate() {
+            let pt = Point::new(idx as i32 % map.width, idx as i32 / map.width);
+            let mut nearest = (0, f32::MAX);
+
+            for (seed_idx, seed) in seeds.iter().enumerate() {
+                let distance = self.distance_algorithm.distance2d(pt, *seed);
+
+                if distance < nearest.1 {
+                    nearest = (seed_idx, distance);
+                }
+            }
+
+            *member = nearest.0;
+        }
+
+        // wall off the cells: a tile becomes wall when the cell to its
+        // right or below differs, which keeps the borders one tile thick.
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let idx = map.xy_idx(x, y);
+                let right = map.xy_idx(x + 1, y);
+                let below = map.xy_idx(x, y + 1);
+
+                map.tiles[idx] = if membership[idx] != membership[right] ||
+                                         membership[idx] != membership[below] {
+                    TileType::Wall
+                } else {
+                    TileType::Floor
+                };
+            }
+        }
+
+        history.take_snapshot(map);
+        add_doorways(rng, map, &membership);
+        history.take_snapshot(map);
+
+        let start_idx = find_central_floor(map)
+            .expect("Voronoi map has no floor to start on");
+        build_data.starting_position = Some(Position{
+            x: start_idx as i32 % map.width,
+            y: start_idx as i32 / map.width,
+        });
+
+        remove_unreachable_areas_returning_most_distant(map, start_idx);
+
+        let mut regions: Vec<Vec<usize>> = vec![Vec::new(); SEED_COUNT];
+
+        for (idx, tile) in map.tiles.iter().enumerate() {
+            if *tile == TileType::Floor {
+                regions[membership[idx]].push(idx);
+            }
+        }
+
+        // cells lost to the unreachable areas have nothing to spawn on
+        regions.retain(|region| !region.is_empty());
+        build_data.spawn_regions = Some(regions);
+    }
+}
+
+/// Opens one gap in the wall between each pair of neighbouring
+/// cells, so the hive can be walked from end to end.
+fn add_doorways(rng: &mut RandomNumberGenerator, map: &mut Map, membership: &[usize]) {
+    // ordered, so the same seed always opens the same doorways
+    let mut candidates: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+
+    for y in 1..map.height - 1 {
+        for x in 1..map.width - 1 {
+            let idx = map.xy_idx(x, y);
+
+            if map.tiles[idx] != TileType::Wall {
+                continue;
+            }
+
+            // a wall is a doorway candidate if it has floor from two
+            // different cells on opposite sides of it.
+            for (a, b) in [(map.xy_idx(x - 1, y), map.xy_idx(x + 1, y)),
+                           (map.xy_idx(x, y - 1), map.xy_idx(x, y + 1))] {
+                if map.tiles[a] == TileType::Floor &&
+                   map.tiles[b] == TileType::Floor &&
+                   membership[a] != membership[b] {
+                    let key = (membership[a].min(membership[b]),
+                               membership[a].max(membership[b]));
+                    candidates.entry(key).or_default().push(idx);
+                }
+            }
+        }
+    }
+
+    for walls in candidates.values() {
+        let idx = walls[rng.random_slice_index(walls).unwrap()];
+        map.tiles[idx] = TileType::Floor;
+    }
+}
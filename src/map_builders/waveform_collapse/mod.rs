@@ -0,0 +1,112 @@
+use super::{ InitialMapBuilder, BuilderMap, Map, TileType, Position, SimpleMapBuilder,
+             remove_unreachable_areas_returning_most_distant,
+             find_central_floor, count_floor };
+
+use rltk::RandomNumberGenerator;
+
+mod patterns;
+use patterns::{ Sample, Patterns, NORTH, EAST, SOUTH, WEST };
+mod solver;
+use solver::Solver;
+mod samples;
+
+const MAX_ATTEMPTS: i32 = 10; // contradictions tolerated before falling back
+const MIN_FLOOR_PERCENT: f32 = 0.2; // smaller results count as a failure too
+
+/// Synthesises a level from the local tile patterns of a small sample
+/// layout. If it can't produce a playable map after `MAX_ATTEMPTS`, it
+/// builds a rooms-and-corridors level instead.
+pub struct WaveformCollapseBuilder {
+    sample: &'static str,
+    chunk_size: i32,
+}
+
+impl InitialMapBuilder for WaveformCollapseBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl WaveformCollapseBuilder {
+    pub fn new(sample: &'static str, chunk_size: i32) -> Box<WaveformCollapseBuilder> {
+        Box::new(WaveformCollapseBuilder { sample, chunk_size })
+    }
+
+    pub fn rooms() -> Box<WaveformCollapseBuilder> {
+        WaveformCollapseBuilder::new(samples::ROOMS_SAMPLE, 4)
+    }
+
+    pub fn caverns() -> Box<WaveformCollapseBuilder> {
+        WaveformCollapseBuilder::new(samples::CAVERN_SAMPLE, 3)
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let sample = Sample::parse(self.sample);
+        let patterns = Patterns::build(&sample, self.chunk_size);
+
+        for _ in 0..MAX_ATTEMPTS {
+            if self.attempt(rng, &patterns, build_data) {
+                return;
+            }
+        }
+
+        // the fallback leaves the start to the rest of the chain, so
+        // put the player in its first room as it used to.
+        SimpleMapBuilder::new().build_map(rng, build_data);
+        let (start_x, start_y) = build_data.map.rooms[0].center();
+        build_data.starting_position = Some(Position{ x: start_x, y: start_y });
+    }
+
+    /// Tries to fill the map once, returning true if it succeeded.
+    fn attempt(&mut self, rng: &mut RandomNumberGenerator, patterns: &Patterns,
+               build_data: &mut BuilderMap) -> bool {
+        let mut map = Map::new(build_data.map.depth, build_data.map.width, build_data.map.height);
+
+        let mut solver = Solver::new(patterns, &map);
+
+        if !solver.solve(rng) {
+            return false;
+        }
+
+        solver.render(&mut map);
+        build_data.history.take_snapshot(&map);
+
+        // the sample's own edges can end up on the map's border, so
+        // seal it off again before anything walks on it.
+        for x in 0..map.width {
+            let top = map.xy_idx(x, 0);
+            let bottom = map.xy_idx(x, map.height - 1);
+            map.tiles[top] = TileType::Wall;
+            map.tiles[bottom] = TileType::Wall;
+        }
+
+        for y in 0..map.height {
+            let left = map.xy_idx(0, y);
+            let right = map.xy_idx(map.width - 1, y);
+            map.tiles[left] = TileType::Wall;
+            map.tiles[right] = TileType::Wall;
+        }
+
+        let start_idx = match find_central_floor(&map) {
+            Some(idx) => idx,
+            None => return false,
+        };
+
+        remove_unreachable_areas_returning_most_distant(&mut map, start_idx);
+        build_data.history.take_snapshot(&map);
+
+        let floor = count_floor(&map);
+
+        if (floor as f32) < map.tiles.len() as f32 * MIN_FLOOR_PERCENT {
+            return false;
+        }
+
+        build_data.starting_position = Some(Position{
+            x: start_idx as i32 % map.width,
+            y: start_idx as i32 / map.width,
+        });
+        build_data.map = map;
+
+        true
+    }
+}
@@ -0,0 +1,144 @@
+use super::TileType;
+
+use std::collections::{ HashMap, BTreeSet };
+
+// the order directions are stored in, clockwise from north
+pub const NORTH: usize = 0;
+pub const EAST: usize = 1;
+pub const SOUTH: usize = 2;
+pub const WEST: usize = 3;
+
+/// A small hand-drawn layout that the generator learns from.
+/// `#` is a wall, and anything else is floor.
+pub struct Sample {
+    pub width: i32,
+    pub height: i32,
+    pub tiles: Vec<TileType>,
+}
+
+impl Sample {
+    pub fn parse(text: &str) -> Sample {
+        let rows: Vec<&str> = text.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        let mut tiles = Vec::with_capacity(width * rows.len());
+
+        for row in rows.iter() {
+            let mut chars = row.chars();
+
+            // short rows are padded out with wall
+            for _ in 0..width {
+                tiles.push(match chars.next() {
+                    Some('#') | None => TileType::Wall,
+                    Some(_) => TileType::Floor,
+                });
+            }
+        }
+
+        Sample{ width: width as i32, height: rows.len() as i32, tiles }
+    }
+
+    fn tile(&self, x: i32, y: i32) -> TileType {
+        self.tiles[(y * self.width + x) as usize]
+    }
+
+    /// The sample mirrored left to right and/or top to bottom.
+    fn flipped(&self, flip_x: bool, flip_y: bool) -> Sample {
+        let mut tiles = Vec::with_capacity(self.tiles.len());
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let src_x = if flip_x { self.width - 1 - x } else { x };
+                let src_y = if flip_y { self.height - 1 - y } else { y };
+                tiles.push(self.tile(src_x, src_y));
+            }
+        }
+
+        Sample{ width: self.width, height: self.height, tiles }
+    }
+
+    fn chunk(&self, x: i32, y: i32, chunk_size: i32) -> Vec<TileType> {
+        let mut chunk = Vec::with_capacity((chunk_size * chunk_size) as usize);
+
+        for chunk_y in 0..chunk_size {
+            for chunk_x in 0..chunk_size {
+                chunk.push(self.tile(x + chunk_x, y + chunk_y));
+            }
+        }
+
+        chunk
+    }
+}
+
+/// Every distinct chunk found in the samples, how often it turned up,
+/// and which chunks were seen next to it in each direction.
+pub struct Patterns {
+    pub chunk_size: i32,
+    pub chunks: Vec<Vec<TileType>>,
+    pub weights: Vec<i32>,
+    pub compatible: Vec<[Vec<usize>; 4]>,
+}
+
+impl Patterns {
+    /// Cuts the sample (and its mirror images) into chunks at every
+    /// offset, recording which chunk sits `chunk_size` tiles to the
+    /// right of and below each one.
+    pub fn build(sample: &Sample, chunk_size: i32) -> Patterns {
+        let mut ids: HashMap<Vec<TileType>, usize> = HashMap::new();
+        let mut chunks: Vec<Vec<TileType>> = Vec::new();
+        let mut weights: Vec<i32> = Vec::new();
+        let mut pairs: [BTreeSet<(usize, usize)>; 2] = [BTreeSet::new(), BTreeSet::new()];
+
+        let variants = [sample.flipped(false, false), sample.flipped(true, false),
+                        sample.flipped(false, true), sample.flipped(true, true)];
+
+        for variant in variants.iter() {
+            for y in 0..=variant.height - chunk_size {
+                for x in 0..=variant.width - chunk_size {
+                    let id = intern(&mut ids, &mut chunks, variant.chunk(x, y, chunk_size));
+                    weights.resize(chunks.len(), 0);
+                    weights[id] += 1;
+
+                    if x + chunk_size * 2 <= variant.width {
+                        let right = variant.chunk(x + chunk_size, y, chunk_size);
+                        pairs[0].insert((id, intern(&mut ids, &mut chunks, right)));
+                    }
+
+                    if y + chunk_size * 2 <= variant.height {
+                        let below = variant.chunk(x, y + chunk_size, chunk_size);
+                        pairs[1].insert((id, intern(&mut ids, &mut chunks, below)));
+                    }
+                }
+            }
+        }
+
+        weights.resize(chunks.len(), 0);
+
+        let mut compatible: Vec<[Vec<usize>; 4]> = vec![Default::default(); chunks.len()];
+
+        for (left, right) in pairs[0].iter() {
+            compatible[*left][EAST].push(*right);
+            compatible[*right][WEST].push(*left);
+        }
+
+        for (top, bottom) in pairs[1].iter() {
+            compatible[*top][SOUTH].push(*bottom);
+            compatible[*bottom][NORTH].push(*top);
+        }
+
+        Patterns{ chunk_size, chunks, weights, compatible }
+    }
+}
+
+/// Returns the id of `chunk`, adding it to the list if it is new. Ids
+/// are handed out in scan order, so they are the same from run to run.
+fn intern(ids: &mut HashMap<Vec<TileType>, usize>, chunks: &mut Vec<Vec<TileType>>,
+          chunk: Vec<TileType>) -> usize {
+    *ids.entry(chunk.clone()).or_insert_with(|| {
+        chunks.push(chunk);
+        chunks.len() - 1
+    })
+}
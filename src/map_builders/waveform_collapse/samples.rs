@@ -0,0 +1,53 @@
+// Sample layouts for the wave function collapse builder. Each one is
+// read with `Sample::parse`: `#` is a wall and `.` is floor. The
+// generator only ever reproduces arrangements it can find in here, so
+// a sample wants plenty of variety for its size.
+
+/// Square rooms joined by straight, narrow corridors.
+pub const ROOMS_SAMPLE: &str = "
+##############################
+#.....######........#####....#
+#.....######........#####....#
+#............................#
+#.....######........#####....#
+#.....######........#####....#
+###.########........######.###
+###.################.#####.###
+###.################.#####.###
+###.###.......######.###.....#
+###.###.......######.###.....#
+#...###..............###.....#
+#.#####.......######.........#
+#.#####.......######.###.....#
+#.#############.####.###.....#
+#.#############.####.#########
+#.......#######.####.#########
+#.......#######.......########
+#..........####.......########
+#.......#######.......########
+##############################
+";
+
+/// Rounded caverns that pinch together at narrow throats.
+pub const CAVERN_SAMPLE: &str = "
+##############################
+####...#######################
+##.......#####.....###########
+#.........###.......####...###
+#..........#.........#.......#
+##...................#.......#
+###........###...............#
+####......#####.....##......##
+#####....#######...####....###
+######..#########..####...####
+######...#######...###....####
+#####.....#####.....#.....####
+####.......###............####
+###.........................##
+##.....##........###.........#
+##....####......#####........#
+###..######....#######......##
+####..####.....########....###
+#####......##########.......##
+##############################
+";
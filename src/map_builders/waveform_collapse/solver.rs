@@ -0,0 +1,169 @@
+use super::{ Map, Patterns, NORTH, EAST, SOUTH, WEST };
+
+use rltk::RandomNumberGenerator;
+
+/// Collapses a grid of chunk-sized cells, one at a time, until every
+/// cell has exactly one pattern left or a cell runs out of options.
+pub struct Solver<'a> {
+    patterns: &'a Patterns,
+    width: i32,
+    height: i32,
+    wave: Vec<Vec<bool>>,
+    remaining: Vec<usize>,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(patterns: &'a Patterns, map: &Map) -> Solver<'a> {
+        let width = map.width / patterns.chunk_size;
+        let height = map.height / patterns.chunk_size;
+        let cells = (width * height) as usize;
+        let pattern_count = patterns.chunks.len();
+
+        Solver {
+            patterns,
+            width,
+            height,
+            wave: vec![vec![true; pattern_count]; cells],
+            remaining: vec![pattern_count; cells],
+        }
+    }
+
+    /// Runs the solver to completion, returning false on a contradiction.
+    pub fn solve(&mut self, rng: &mut RandomNumberGenerator) -> bool {
+        // patterns that were never seen with a neighbour on some side
+        // can't sit in the middle of the map; strip those out first.
+        let everything: Vec<usize> = (0..self.wave.len()).collect();
+
+        if !self.propagate(everything) {
+            return false;
+        }
+
+        while let Some(cell) = self.lowest_entropy_cell(rng) {
+            self.collapse(rng, cell);
+
+            if !self.propagate(vec![cell]) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Copies the chosen pattern of every cell onto the map. Anything
+    /// past the last whole cell is left as it was.
+    pub fn render(&self, map: &mut Map) {
+        let chunk_size = self.patterns.chunk_size;
+
+        for (cell, options) in self.wave.iter().enumerate() {
+            let pattern = match options.iter().position(|possible| *possible) {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+
+            let cell_x = (cell as i32 % self.width) * chunk_size;
+            let cell_y = (cell as i32 / self.width) * chunk_size;
+
+            for (i, tile) in self.patterns.chunks[pattern].iter().enumerate() {
+                let idx = map.xy_idx(cell_x + i as i32 % chunk_size,
+                                     cell_y + i as i32 / chunk_size);
+                map.tiles[idx] = *tile;
+            }
+        }
+    }
+
+    /// Picks one of the undecided cells with the fewest options left.
+    fn lowest_entropy_cell(&self, rng: &mut RandomNumberGenerator) -> Option<usize> {
+        let fewest = self.remaining.iter().filter(|count| **count > 1).min()?;
+
+        let candidates: Vec<usize> = self.remaining.iter()
+            .enumerate()
+            .filter(|(_, count)| *count == fewest)
+            .map(|(cell, _)| cell)
+            .collect();
+
+        Some(candidates[rng.random_slice_index(&candidates).unwrap()])
+    }
+
+    /// Settles on a single pattern for the cell, weighted by how often
+    /// each remaining pattern appeared in the sample.
+    fn collapse(&mut self, rng: &mut RandomNumberGenerator, cell: usize) {
+        let options: Vec<usize> = self.possible(cell);
+        let total: i32 = options.iter().map(|p| self.patterns.weights[*p]).sum();
+        let mut roll = rng.roll_dice(1, total);
+        let mut chosen = options[0];
+
+        for pattern in options.iter() {
+            roll -= self.patterns.weights[*pattern];
+
+            if roll <= 0 {
+                chosen = *pattern;
+                break;
+            }
+        }
+
+        for (pattern, possible) in self.wave[cell].iter_mut().enumerate() {
+            *possible = pattern == chosen;
+        }
+
+        self.remaining[cell] = 1;
+    }
+
+    /// Removes options from the neighbours of every changed cell until
+    /// nothing else changes. Returns false if a cell is left empty.
+    fn propagate(&mut self, mut stack: Vec<usize>) -> bool {
+        let mut allowed = vec![false; self.patterns.chunks.len()];
+
+        while let Some(cell) = stack.pop() {
+            let x = cell as i32 % self.width;
+            let y = cell as i32 / self.width;
+
+            for (direction, dx, dy) in [(NORTH, 0, -1), (EAST, 1, 0), (SOUTH, 0, 1), (WEST, -1, 0)] {
+                let (nx, ny) = (x + dx, y + dy);
+
+                if nx < 0 || nx >= self.width || ny < 0 || ny >= self.height {
+                    continue;
+                }
+
+                // everything any of our options allows on that side
+                allowed.iter_mut().for_each(|a| *a = false);
+
+                for (pattern, possible) in self.wave[cell].iter().enumerate() {
+                    if *possible {
+                        for neighbour in self.patterns.compatible[pattern][direction].iter() {
+                            allowed[*neighbour] = true;
+                        }
+                    }
+                }
+
+                let neighbour_cell = (ny * self.width + nx) as usize;
+                let mut changed = false;
+
+                for (pattern, possible) in self.wave[neighbour_cell].iter_mut().enumerate() {
+                    if *possible && !allowed[pattern] {
+                        *possible = false;
+                        self.remaining[neighbour_cell] -= 1;
+                        changed = true;
+                    }
+                }
+
+                if self.remaining[neighbour_cell] == 0 {
+                    return false;
+                }
+
+                if changed {
+                    stack.push(neighbour_cell);
+                }
+            }
+        }
+
+        true
+    }
+
+    fn possible(&self, cell: usize) -> Vec<usize> {
+        self.wave[cell].iter()
+            .enumerate()
+            .filter(|(_, possible)| **possible)
+            .map(|(pattern, _)| pattern)
+            .collect()
+    }
+}
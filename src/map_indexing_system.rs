@@ -1,4 +1,4 @@
-use super::{ Map, Position, BlocksTile };
+use super::{ Map, Position, BlocksTile, Door };
 use specs::prelude::*;
 
 pub struct MapIndexingSystem{}
@@ -7,10 +7,11 @@ impl<'a> System<'a> for MapIndexingSystem {
     type SystemData = (WriteExpect<'a, Map>,
                        ReadStorage<'a, Position>,
                        ReadStorage<'a, BlocksTile>,
+                       ReadStorage<'a, Door>,
                        Entities<'a>);
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, position, blockers, entities) = data;
+        let (mut map, position, blockers, doors, entities) = data;
 
         map.populate_blocked();
         map.clear_content_index();
@@ -25,6 +26,14 @@ impl<'a> System<'a> for MapIndexingSystem {
                 map.blocked[idx] = true;
             }
 
+            // closed doors block both movement and sight
+            if let Some(door) = doors.get(entity) {
+                if !door.open {
+                    map.blocked[idx] = true;
+                    map.closed_doors[idx] = true;
+                }
+            }
+
             // push the entity to the appropriate index slot.
             // This is a copy of the entity, so there is no need to Clone
             map.tile_content[idx].push(entity);
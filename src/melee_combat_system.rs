@@ -1,8 +1,6 @@
 use super::{ CombatStats, WantsToMelee, Name, SufferDamage, GameLog};
 use specs::prelude::*;
 
-use rltk::console;
-
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
@@ -1,7 +1,6 @@
-use crate::{player, WantsToMelee, RunState};
-
-use super::{ Viewshed, Monster, Name, Map, Position };
-use rltk::{ field_of_view, Point, console};
+use super::{ Viewshed, Monster, Map, Position, WantsToMelee, RunState,
+             Door, Renderable, Confusion };
+use rltk::{ Point };
 use specs::prelude::*;
 
 pub struct MonsterAI {}
@@ -16,32 +15,52 @@ impl<'a> System<'a> for MonsterAI {
                        WriteStorage<'a, Viewshed>,
                        ReadStorage<'a, Monster>,
                        WriteStorage<'a, Position>,
-                       WriteStorage<'a, WantsToMelee>);
+                       WriteStorage<'a, WantsToMelee>,
+                       WriteStorage<'a, Door>,
+                       WriteStorage<'a, Renderable>,
+                       WriteStorage<'a, Confusion>);
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, player_pos, player_entity, runstate, entities, mut viewshed, monster, mut position, mut wants_to_melee) = data;
+        let (mut map, player_pos, player_entity, runstate, entities, mut viewshed, monster, mut position, mut wants_to_melee, mut doors, mut renderables, mut confused) = data;
 
         if *runstate != RunState::MonsterTurn {
             return;
         }
 
-        for (entity, mut viewshed, _monster, mut pos) in 
+        let mut opened_doors: Vec<Point> = Vec::new();
+
+        for (entity, viewshed, _monster, pos) in 
                             (&entities, &mut viewshed, &monster, &mut position).join() {
-            
+
+            // a confused monster loses its turn until it wears off
+            if let Some(confusion) = confused.get_mut(entity) {
+                confusion.turns -= 1;
+
+                if confusion.turns < 1 {
+                    confused.remove(entity);
+                }
+
+                continue;
+            }
+
             let distance = rltk::DistanceAlg::Pythagoras.distance2d(
                 Point::new(pos.x, pos.y), *player_pos);
 
             if distance < 1.5 {
                 wants_to_melee.insert(entity, WantsToMelee { target: *player_entity }).expect("Unable to insert attack");
-            }
-
-            if viewshed.visible_tiles.contains(&*player_pos) {
+            } else if viewshed.visible_tiles.contains(&*player_pos) {
                 let path = rltk::a_star_search(
                                     map.xy_idx(pos.x, pos.y),
                                     map.xy_idx(player_pos.x, player_pos.y),
-                                    &mut *map);
+                                    &*map);
 
-                if path.success && path.steps.len() > 1 {
+                if path.success && path.steps.len() > 1 && map.closed_doors[path.steps[1]] {
+                    // a closed door in the way takes the turn to open
+                    let door_idx = path.steps[1];
+                    open_door(&mut map, door_idx, &mut doors, &mut renderables);
+                    opened_doors.push(Point::new(door_idx as i32 % map.width,
+                                                 door_idx as i32 / map.width));
+                } else if path.success && path.steps.len() > 1 {
                     let mut idx = map.xy_idx(pos.x, pos.y);
                     map.blocked[idx] = false;
 
@@ -54,5 +73,32 @@ impl<'a> System<'a> for MonsterAI {
                 }
             }
         }
+
+        // anyone who could see a door that just opened needs to look again
+        if !opened_doors.is_empty() {
+            for viewshed in (&mut viewshed).join() {
+                if opened_doors.iter().any(|door| viewshed.visible_tiles.contains(door)) {
+                    viewshed.dirty = true;
+                }
+            }
+        }
+    }
+}
+
+/// Opens whichever closed door is standing at `idx`, and clears it out
+/// of the way on the map straight away.
+pub fn open_door(map: &mut Map, idx: usize,
+                 doors: &mut WriteStorage<Door>,
+                 renderables: &mut WriteStorage<Renderable>) {
+    for entity in map.tile_content[idx].iter() {
+        if let Some(door) = doors.get_mut(*entity) {
+            door.open = true;
+
+            if let Some(render) = renderables.get_mut(*entity) {
+                render.glyph = rltk::to_cp437('/');
+            }
+        }
     }
-}
\ No newline at end of file
+
+    map.open_door(idx);
+}
@@ -1,19 +1,21 @@
-use super::{Position, Player, Viewshed, TileType, State, Map, 
-            RunState, CombatStats, WantsToMelee };
+use super::{Position, Player, Viewshed, State, Map, TileType,
+            RunState, CombatStats, WantsToMelee, GameLog, Door,
+            Renderable, Item, WantsToPickupItem, open_door };
 
-use rltk::{ VirtualKeyCode, Rltk, Point, console };
+use rltk::{ VirtualKeyCode, Rltk, Point };
 use specs::prelude::*;
 
-use std::cmp::{min, max};
-
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let mut pos = ecs.write_storage::<Position>();
-    let mut players = ecs.write_storage::<Player>();
+    let players = ecs.write_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
     let combat_stats = ecs.read_storage::<CombatStats>();
-    let map = ecs.fetch::<Map>();
+    let mut map = ecs.fetch_mut::<Map>();
     let entities = ecs.entities();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
+    let mut doors = ecs.write_storage::<Door>();
+    let mut renderables = ecs.write_storage::<Renderable>();
+    let mut opened_door: Option<Point> = None;
 
     // iterate through all entities that are 
     // players, positions, and viewsheds, we are going to modify them 
@@ -42,12 +44,23 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             }
         }
 
+        // bumping a closed door opens it instead of moving
+        if map.closed_doors[dest_idx] {
+            open_door(&mut map, dest_idx, &mut doors, &mut renderables);
+            opened_door = Some(Point::new(pos.x + delta_x, pos.y + delta_y));
+            viewshed.dirty = true;
+
+            let mut gamelog = ecs.fetch_mut::<GameLog>();
+            gamelog.entries.push("You open the door.".to_string());
+            continue;
+        }
+
         // collision detection
         // if the player would move to a wall, we allow
         // them to move as CLOSE to the wall as we can.
         if !map.blocked[dest_idx] {
-            pos.x = min(79, max(0, pos.x + delta_x));
-            pos.y = min(49, max(0, pos.y + delta_y));
+            pos.x = (pos.x + delta_x).clamp(0, map.width - 1);
+            pos.y = (pos.y + delta_y).clamp(0, map.height - 1);
 
             viewshed.dirty = true;
 
@@ -58,6 +71,80 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             ppos.y = pos.y;
         }
     }
+
+    // anything that could see the door has a new view now
+    if let Some(door) = opened_door {
+        for viewshed in (&mut viewsheds).join() {
+            if viewshed.visible_tiles.contains(&door) {
+                viewshed.dirty = true;
+            }
+        }
+    }
+}
+
+/// True if the player is standing on the way down. Otherwise
+/// tells them there isn't one.
+pub fn try_next_level(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+
+    if map.tiles[player_idx] == TileType::DownStairs {
+        true
+    } else {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog.entries.push("There is no way down from here.".to_string());
+        false
+    }
+}
+
+/// True if the player is standing on the way up, and there is a level
+/// above to go to. Otherwise tells them why not.
+pub fn try_previous_level(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+
+    let refusal = if map.tiles[player_idx] != TileType::UpStairs {
+        "There is no way up from here."
+    } else if map.depth <= 1 {
+        // a hand-made first level can have stairs going up, but
+        // there is nothing above it
+        "The way up is blocked; there is no leaving the dungeon."
+    } else {
+        return true;
+    };
+
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    gamelog.entries.push(refusal.to_string());
+    false
+}
+
+/// Asks to pick up whatever item is on the player's tile. False if
+/// there is nothing there, which doesn't cost a turn.
+fn get_item(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let player_entity = ecs.fetch::<Entity>();
+    let items = ecs.read_storage::<Item>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+
+    let target_item = map.tile_content[player_idx].iter()
+        .find(|entity| items.contains(**entity));
+
+    match target_item {
+        None => {
+            let mut gamelog = ecs.fetch_mut::<GameLog>();
+            gamelog.entries.push("There is nothing here to pick up.".to_string());
+            false
+        }
+        Some(item) => {
+            let mut pickup = ecs.write_storage::<WantsToPickupItem>();
+            pickup.insert(*player_entity, WantsToPickupItem{ collected_by: *player_entity, item: *item })
+                .expect("Unable to insert want to pickup");
+            true
+        }
+    }
 }
 
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
@@ -96,6 +183,32 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
             VirtualKeyCode::Numpad1 |
             VirtualKeyCode::B => try_move_player(-1, 1, &mut gs.ecs),
 
+            VirtualKeyCode::G => {
+                if !get_item(&mut gs.ecs) {
+                    return RunState::AwaitingInput;
+                }
+            }
+
+            VirtualKeyCode::I => return RunState::ShowInventory,
+            VirtualKeyCode::D => return RunState::ShowDropItem,
+
+            // taking the stairs doesn't cost a turn on the level being left
+            VirtualKeyCode::Period => {
+                if try_next_level(&mut gs.ecs) {
+                    return RunState::NextLevel;
+                }
+
+                return RunState::AwaitingInput;
+            }
+
+            VirtualKeyCode::Comma => {
+                if try_previous_level(&mut gs.ecs) {
+                    return RunState::PreviousLevel;
+                }
+
+                return RunState::AwaitingInput;
+            }
+
             _ => { return RunState::AwaitingInput },
         },
     }
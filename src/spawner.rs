@@ -0,0 +1,291 @@
+use super::{ Position, Renderable, Player, Viewshed, Monster, Name,
+             BlocksTile, CombatStats, Door, Item, Consumable, ProvidesHealing,
+             Ranged, InflictsDamage, AreaOfEffect, Confusion, Rect, Map, TileType };
+
+use rltk::{ RGB, RandomNumberGenerator };
+use specs::prelude::*;
+
+/// Spawns the player and returns their entity.
+pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position{ x: player_x, y: player_y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('@'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 0,
+        })
+        .with(Player{})
+        .with(Viewshed{
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        })
+        .with(Name{ name: "Player".to_string() })
+        .with(CombatStats{
+            max_hp: 30,
+            hp: 30,
+            defense: 2,
+            power: 5
+        })
+        .build()
+}
+
+const ITEM_CHANCE: i32 = 2; // one room or region in this many gets an item
+
+/// Places a single monster in the room, in the center if it is free,
+/// and sometimes an item on another floor tile of it.
+pub fn spawn_room(rng: &mut RandomNumberGenerator, map: &Map, room: &Rect,
+                  spawn_list: &mut Vec<(usize, String)>) {
+    // vaults can leave walls and worse inside a room, along with
+    // monsters of their own, and the stairs may already be down
+    let mut floor: Vec<usize> = (room.y1 + 1..=room.y2)
+        .flat_map(|y| (room.x1 + 1..=room.x2).map(move |x| (x, y)))
+        .map(|(x, y)| map.xy_idx(x, y))
+        .filter(|idx| map.tiles[*idx] == TileType::Floor)
+        .filter(|idx| !spawn_list.iter().any(|(spawn_idx, _)| spawn_idx == idx))
+        .collect();
+
+    let (x, y) = room.center();
+    let center = map.xy_idx(x, y);
+
+    let monster_at = match floor.iter().position(|idx| *idx == center) {
+        Some(i) => Some(i),
+        None => rng.random_slice_index(&floor),
+    };
+
+    if let Some(i) = monster_at {
+        spawn_list.push((floor.swap_remove(i), random_monster(rng)));
+    }
+
+    if rng.roll_dice(1, ITEM_CHANCE) == 1 {
+        if let Some(i) = rng.random_slice_index(&floor) {
+            spawn_list.push((floor[i], random_item(rng)));
+        }
+    }
+}
+
+const MAX_GROUP_SIZE: i32 = 3; // largest group spawned in one region
+
+/// Places a group of monsters on distinct tiles of a spawn region,
+/// such as one cell of a Voronoi map, and sometimes an item too.
+pub fn spawn_region(rng: &mut RandomNumberGenerator, area: &[usize],
+                    spawn_list: &mut Vec<(usize, String)>) {
+    let mut areas: Vec<usize> = area.to_vec();
+    let group_size = i32::min(areas.len() as i32, rng.roll_dice(1, MAX_GROUP_SIZE));
+
+    for _ in 0..group_size {
+        let i = rng.random_slice_index(&areas).unwrap();
+        spawn_list.push((areas.swap_remove(i), random_monster(rng)));
+    }
+
+    if rng.roll_dice(1, ITEM_CHANCE) == 1 {
+        if let Some(i) = rng.random_slice_index(&areas) {
+            spawn_list.push((areas[i], random_item(rng)));
+        }
+    }
+}
+
+const SCATTERED_MONSTERS: i32 = 12; // how many monsters a room-less level gets
+const SCATTERED_ITEMS: i32 = 6; // and how many items
+const MIN_MONSTER_DISTANCE: f32 = 10.0; // no monster starts closer to the player
+
+/// Places monsters and items for maps that have no rooms (caves and
+/// the like), dropping them on random floor tiles a fair distance from
+/// the player and off anything already due to spawn.
+pub fn spawn_scattered(rng: &mut RandomNumberGenerator, map: &Map, player_start: &Position,
+                       spawn_list: &mut Vec<(usize, String)>) {
+    let start = rltk::Point::new(player_start.x, player_start.y);
+
+    let mut candidates: Vec<usize> = map.tiles.iter()
+        .enumerate()
+        .filter(|(idx, tile)| {
+            let pt = rltk::Point::new(*idx as i32 % map.width, *idx as i32 / map.width);
+            **tile == TileType::Floor &&
+                rltk::DistanceAlg::Pythagoras.distance2d(start, pt) > MIN_MONSTER_DISTANCE
+        })
+        .map(|(idx, _)| idx)
+        // prefab sections bring monsters of their own
+        .filter(|idx| !spawn_list.iter().any(|(spawn_idx, _)| spawn_idx == idx))
+        .collect();
+
+    // take each tile out of the pool so no two spawns share one
+    for _ in 0..SCATTERED_MONSTERS {
+        if let Some(i) = rng.random_slice_index(&candidates) {
+            spawn_list.push((candidates.swap_remove(i), random_monster(rng)));
+        }
+    }
+
+    for _ in 0..SCATTERED_ITEMS {
+        if let Some(i) = rng.random_slice_index(&candidates) {
+            spawn_list.push((candidates.swap_remove(i), random_item(rng)));
+        }
+    }
+}
+
+/// Creates the entity named in a spawn list entry on its map tile.
+/// Every monster and item in the game, whatever placed it, is built
+/// here. Monsters are numbered from `monster_count`, which counts the
+/// ones the level has so far.
+pub fn spawn_entity(ecs: &mut World, spawn: &(usize, String), monster_count: &mut i32) {
+    let (x, y);
+
+    {
+        let map = ecs.fetch::<Map>();
+        x = spawn.0 as i32 % map.width;
+        y = spawn.0 as i32 / map.width;
+    }
+
+    // numbered, so the log can tell one goblin from another
+    if matches!(spawn.1.as_ref(), "Goblin" | "Ork") {
+        *monster_count += 1;
+    }
+
+    match spawn.1.as_ref() {
+        "Goblin" => goblin(ecs, x, y, *monster_count),
+        "Ork" => ork(ecs, x, y, *monster_count),
+        "Door" => door(ecs, x, y),
+        "Health Potion" => health_potion(ecs, x, y),
+        "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
+        "Fireball Scroll" => fireball_scroll(ecs, x, y),
+        "Confusion Scroll" => confusion_scroll(ecs, x, y),
+        _ => rltk::console::log(format!("Unable to spawn unknown entity: {}", spawn.1)),
+    }
+}
+
+// populate the dungeon with MONSTERS!
+// g - Goblin
+// o - for WE DA ORKS!
+fn random_monster(rng: &mut RandomNumberGenerator) -> String {
+    match rng.roll_dice(1, 2) {
+        1 => "Goblin".to_string(),
+        _ => "Ork".to_string(),
+    }
+}
+
+// potions turn up as often as all the scrolls put together
+fn random_item(rng: &mut RandomNumberGenerator) -> String {
+    match rng.roll_dice(1, 6) {
+        1..=3 => "Health Potion".to_string(),
+        4 => "Magic Missile Scroll".to_string(),
+        5 => "Fireball Scroll".to_string(),
+        _ => "Confusion Scroll".to_string(),
+    }
+}
+
+fn goblin(ecs: &mut World, x: i32, y: i32, number: i32) {
+    monster(ecs, x, y, rltk::to_cp437('g'), "Goblin", number);
+}
+
+fn ork(ecs: &mut World, x: i32, y: i32, number: i32) {
+    monster(ecs, x, y, rltk::to_cp437('o'), "Ork", number);
+}
+
+fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32,
+                        glyph: rltk::FontCharType, name: S, number: i32) {
+    ecs.create_entity()
+        .with(Position{ x, y })
+        .with(Renderable{
+            glyph,
+            fg: RGB::named(rltk::RED),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 1,
+        })
+        .with(Viewshed{
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true
+        })
+        .with(Monster{})
+        .with(Name{ name: format!("{} #{}", name.to_string(), number) })
+        .with(BlocksTile{})
+        .with(CombatStats {
+            max_hp: 16,
+            hp: 16,
+            defense: 2,
+            power: 5,
+        })
+        .build();
+}
+
+fn door(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position{ x, y })
+        .with(Renderable{
+            glyph: rltk::to_cp437('+'),
+            fg: RGB::named(rltk::CHOCOLATE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name{ name: "Door".to_string() })
+        .with(Door{ open: false })
+        .build();
+}
+
+fn health_potion(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position{ x, y })
+        .with(Renderable{
+            glyph: rltk::to_cp437('¡'),
+            fg: RGB::named(rltk::MAGENTA),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name{ name: "Health Potion".to_string() })
+        .with(Item{})
+        .with(Consumable{})
+        .with(ProvidesHealing{ heal_amount: 8 })
+        .build();
+}
+
+fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position{ x, y })
+        .with(Renderable{
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::CYAN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name{ name: "Magic Missile Scroll".to_string() })
+        .with(Item{})
+        .with(Consumable{})
+        .with(Ranged{ range: 6 })
+        .with(InflictsDamage{ damage: 8 })
+        .build();
+}
+
+fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position{ x, y })
+        .with(Renderable{
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name{ name: "Fireball Scroll".to_string() })
+        .with(Item{})
+        .with(Consumable{})
+        .with(Ranged{ range: 6 })
+        .with(InflictsDamage{ damage: 20 })
+        .with(AreaOfEffect{ radius: 3 })
+        .build();
+}
+
+fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position{ x, y })
+        .with(Renderable{
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::PINK),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name{ name: "Confusion Scroll".to_string() })
+        .with(Item{})
+        .with(Consumable{})
+        .with(Ranged{ range: 6 })
+        .with(Confusion{ turns: 4 })
+        .build();
+}
@@ -65,7 +65,7 @@ impl<'a> System<'a> for VisibilitySystem {
                 // care about.
                 let p: Option<&Player> = player.get(ent);
 
-                if let Some(p) = p {
+                if let Some(_p) = p {
                     // set all visible tiles in the map to false
                     for t in map.visible_tiles.iter_mut() {
                         *t = false;